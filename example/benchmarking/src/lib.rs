@@ -83,17 +83,17 @@ macro_rules! gen_celltype {
                     Ok(())
                 }
 
-                fn genome_from_json(&self, _jsdata: JsonData) -> Genome{
-                    Genome::new()
+                fn genome_from_json(&self, jsdata: JsonData) -> Result<Genome, Errcode>{
+                    Ok(serde_json::from_str(&jsdata)?)
                 }
 
-                fn genome_to_json(&self, genome: Genome) -> JsonData{
-                    serde_json::to_string(&genome).unwrap()
+                fn genome_to_json(&self, genome: Genome) -> Result<JsonData, Errcode>{
+                    Ok(serde_json::to_string(&genome)?)
                 }
 
                 fn create_cell_from_genome(&self, genome: &Genome) -> Self::CellType{
                     $name {
-                        celldata: CellData { genome: genome.clone(), score: 0.0, version:1},
+                        celldata: CellData { genome: genome.clone(), score: 0.0, version: $name::genome_version()},
                         math_fct: self.math_fct.clone()
                     }
                 }
@@ -129,10 +129,16 @@ macro_rules! gen_celltype {
         }
 
         impl Cell for $name{
+            type Data = GenalgoData;
+
             fn get_genome_length() -> usize{
                 $ndim
             }
 
+            fn genome_version() -> u64{
+                1
+            }
+
             fn genome_version_adapt(genome: &Genome, _version: u64) -> Genome{
                 genome.clone()
             }
@@ -4,10 +4,19 @@ use petridish::generate_py_ifaces;
 use petridish::cell::*;
 use petridish::lab::Lab;
 use petridish::dataset::GenalgoData;
+use petridish::genome::{Genome, GenomeParams};
+use petridish::gp::{GpFunction, GpSpec, PrimitiveSet, SymbolicRegressionAlgo, SymbolicRegressionCell};
+use petridish::neuro::{Activation, LayerSpec, Loss, MlpAlgo, MlpCell, MlpSpec, Topology};
 
 use serde_json;
 
-const KEY_LIST: [&str; 4] = ["parameter1", "parameter2", "parameter3", "parameter4"];
+#[derive(Genome, Clone)]
+pub struct TestParams{
+    parameter1: f64,
+    parameter2: f64,
+    parameter3: f64,
+    parameter4: f64,
+}
 
 #[derive(Clone)]
 pub struct TestAlgoA{
@@ -33,18 +42,16 @@ impl Algo for TestAlgoA{
         Ok(())
     }
 
-    fn genome_from_json(&self, jsdata: JsonData) -> Genome{
-        __genome_from_json(jsdata, &KEY_LIST.to_vec())
+    fn genome_from_json(&self, jsdata: JsonData) -> Result<Genome, Errcode>{
+        TestParams::genome_from_json(&jsdata)
     }
 
-    fn genome_to_json(&self, genome: Genome) -> JsonData{
-        __genome_to_json(genome, &KEY_LIST.to_vec())
+    fn genome_to_json(&self, genome: Genome) -> Result<JsonData, Errcode>{
+        TestParams::genome_to_json(&genome)
     }
 
     fn create_cell_from_genome(&self, genome: &Genome) -> Self::CellType{
-        TestCell {
-            celldata: CellData { genome: genome.clone(), score: 0.0, version: 1},
-        }
+        TestCell::new(genome)
     }
 
     fn check_generation_over(&self, genalgo: &Lab<TestCell>) -> bool{
@@ -85,18 +92,16 @@ impl Algo for TestAlgoB{
         Ok(())
     }
 
-    fn genome_from_json(&self, jsdata: JsonData) -> Genome{
-        __genome_from_json(jsdata, &KEY_LIST.to_vec())
+    fn genome_from_json(&self, jsdata: JsonData) -> Result<Genome, Errcode>{
+        TestParams::genome_from_json(&jsdata)
     }
 
-    fn genome_to_json(&self, genome: Genome) -> JsonData{
-        __genome_to_json(genome, &KEY_LIST.to_vec())
+    fn genome_to_json(&self, genome: Genome) -> Result<JsonData, Errcode>{
+        TestParams::genome_to_json(&genome)
     }
 
     fn create_cell_from_genome(&self, genome: &Genome) -> Self::CellType{
-        TestCell {
-            celldata: CellData { genome: genome.clone(), score: 0.0, version: 1},
-        }
+        TestCell::new(genome)
     }
 
     fn check_generation_over(&self, genalgo: &Lab<TestCell>) -> bool{
@@ -117,11 +122,27 @@ impl Algo for TestAlgoB{
 #[derive(Clone)]
 pub struct TestCell{
     celldata: CellData,
+    params: TestParams,
+}
+
+impl TestCell{
+    fn new(genome: &Genome) -> TestCell{
+        TestCell {
+            celldata: CellData { genome: genome.clone(), score: 0.0, version: Self::genome_version()},
+            params: TestParams::from_genome(genome).expect("Wrong genome length"),
+        }
+    }
 }
 
 impl Cell for TestCell{
+    type Data = GenalgoData;
+
     fn get_genome_length() -> usize{
-        KEY_LIST.len()
+        TestParams::genome_length()
+    }
+
+    fn genome_version() -> u64{
+        1
     }
 
     fn genome_version_adapt(genome: &Genome, version: u64) -> Genome{
@@ -133,20 +154,63 @@ impl Cell for TestCell{
     }
 
     fn action(&mut self, data: &GenalgoData){
-        self.celldata.score = {
-            let mut res = 0.0;
-            for g in self.celldata.genome.iter(){
-                res += (g-0.5).abs();
-            }
-            res
-        };
+        let p = &self.params;
+        self.celldata.score = (p.parameter1 - 0.5).abs() + (p.parameter2 - 0.5).abs()
+            + (p.parameter3 - 0.5).abs() + (p.parameter4 - 0.5).abs();
     }
 
     fn reset(&mut self, genome: &Genome){
         self.celldata.genome = genome.clone();
+        self.params = TestParams::from_genome(genome).expect("Wrong genome length");
     }
 }
 
+// Symbolic regression of the last column of the dataset rows from the first two ones
+pub struct RegressionSpec;
+
+impl GpSpec for RegressionSpec{
+    fn primitive_set() -> PrimitiveSet{
+        PrimitiveSet {
+            functions: vec![GpFunction::Add, GpFunction::Sub, GpFunction::Mul, GpFunction::Sin],
+            nvars: 2,
+            constants: Some((-1.0, 1.0)),
+        }
+    }
+
+    fn parsimony() -> f64{
+        1e-4
+    }
+}
+
+pub type RegressionCell = SymbolicRegressionCell<RegressionSpec>;
+
+// Network of 2 inputs, a hidden layer of 4 neurons and a probability as output, for XOR
+pub struct XorSpec;
+
+impl MlpSpec for XorSpec{
+    fn topology() -> Topology{
+        Topology {
+            inputs: 2,
+            layers: vec![
+                LayerSpec { size: 4, activation: Activation::Tanh },
+                LayerSpec { size: 1, activation: Activation::Sigmoid },
+            ],
+        }
+    }
+
+    fn loss() -> Loss{
+        Loss::BinaryCrossEntropy
+    }
+
+    fn weight_scale() -> f64{
+        5.0
+    }
+}
+
+pub type XorCell = MlpCell<XorSpec>;
+
 generate_py_ifaces!(petridish,
     [test] TestCell => (A => TestAlgoA, B => TestAlgoB),
+    [regression] RegressionCell => (sr => SymbolicRegressionAlgo<RegressionSpec>),
+    [xor] XorCell => (mlp => MlpAlgo<XorSpec>),
 );
@@ -69,6 +69,59 @@ def test_create_from_map(ngens=4):
     lab.apply_map_with_algo_A(json.dumps(labmap))
     run_lab(lab)
 
+def test_map_assistant_options():
+    lab = create_lab_object()
+    labast = genalgo.create_labmap_assistant("RingFormat")
+    labast.disable_random_opti()
+    labast.set_priorities(1.0, 0.8, 1.5)
+    labast.add_final_tail("Final", 1.0, "Darwin", "")
+    labast.add_final_tail_fixed_size("Refine", 200, "Darwin", "")
+    for i in range(0, 4, 2):
+        labast.add_opti_part_fixed_size("ISO" + chr(ord("A") + i), [i, i+1], 300, "Darwin", "")
+    labmap = labast.generate_map("Darwin")
+    lab.apply_map_with_algo_A(labmap)
+    lab.set_output_algorithm(labast.get_output_index())
+    print(genalgo.map_to_mermaid(labmap))
+    run_lab(lab)
+
+def test_sensitivity_map():
+    lab = create_lab_object()
+    analysis = lab.analyse_sensitivity_with_algo_A(genalgo.get_sensitivity_default())
+    print("Gene groups: {}".format(json.loads(analysis)["groups"]))
+    labast = genalgo.create_labmap_assistant("WheelFormat")
+    labast.add_sensitivity_parts(analysis, "Darwin", "")
+    lab.apply_map_with_algo_A(labast.generate_map("Darwin"))
+    lab.set_output_algorithm(labast.get_output_index())
+    run_lab(lab)
+
+TOML_MAP = """
+output = "Final"
+
+[[algos]]
+id = "Final"
+method = "Darwin"
+weight = 0.5
+give = ["ISO_A", "ISO_B"]
+
+[[algos]]
+id = "ISO_A"
+method = "Darwin"
+impr_genes = [0, 1]
+options = { gene_reroll_proba = 0.3 }
+give = ["Final"]
+
+[[algos]]
+id = "ISO_B"
+method = "Darwin"
+impr_genes = [2, 3]
+give = ["Final"]
+"""
+
+def test_toml_map():
+    print(genalgo.map_file_to_json(TOML_MAP, "toml"))
+    lab = create_lab_object()
+    lab.apply_toml_map_with_algo_A(TOML_MAP)
+    run_lab(lab)
 
 def test_empty_opti():
     obj = create_lab_object()
@@ -77,13 +130,200 @@ def test_empty_opti():
     obj.configure_algo(algo_a_ind, algo_config)
     run_lab(obj)
 
+def test_minibatch_dataset(path="/tmp/petridish_minibatch.csv"):
+    with open(path, "w") as f:
+        f.write("x;label\n")
+        for i in range(200):
+            f.write("{};{}\n".format(i / 200, i % 2))
+    lab = create_lab_object()
+    lab.register_csv_dataset("train", path, ";", True, [], [])
+    lab.sample_dataset("train", 20, 1, True)
+    algo_a_ind = lab.register_algo_A()
+    lab.configure_algo(algo_a_ind, genalgo.get_algo_default())
+    run_lab(lab)
+
+def test_validation_dataset(path="/tmp/petridish_validation.csv"):
+    with open(path, "w") as f:
+        f.write("x\n")
+        for i in range(50):
+            f.write("{}\n".format(i / 50))
+    lab_options = json.loads(genalgo.get_lab_default())
+    lab_options["npop"] = NPOP
+    lab_options["validation_interval"] = 2
+    lab_options["select_by_validation"] = True
+    lab = genalgo.create_lab_test(json.dumps(lab_options))
+    lab.register_empty_dataset(1)
+    lab.register_csv_dataset("validation", path, ";", True, [], [])
+    lab.set_dataset_role("validation", "Validation")
+    algo_a_ind = lab.register_algo_A()
+    lab.configure_algo(algo_a_ind, genalgo.get_algo_default())
+    run_lab(lab)
+    history = json.loads(lab.get_run_history())
+    print("Training: {}\nValidation: {}".format(history["training"], history["validation"]))
+    print("Overfitting: {}".format(lab.is_overfitting(3)))
+
+def test_python_dataset():
+    lab = create_lab_object()
+    lab.register_dataset("rows", [[i / 10, 1 - (i / 10)] for i in range(10)])
+    def generate():
+        for i in range(5):
+            yield (i, i * 2)
+    lab.register_dataset("generated", generate)
+    lab.set_dataset_role("generated", "Validation")
+    lab.register_dataset("generator", ((i / 5, i / 10) for i in range(5)))
+    try:
+        lab.register_dataset("wrong", 42)
+        raise AssertionError("Registered a dataset neither iterable nor callable")
+    except TypeError:
+        pass
+    print(lab.list_datasets())
+    lab.remove_dataset("empty")
+    algo_a_ind = lab.register_algo_A()
+    lab.configure_algo(algo_a_ind, genalgo.get_algo_default())
+    run_lab(lab)
+
+def test_eval_cache():
+    lab_options = json.loads(genalgo.get_lab_default())
+    lab_options["npop"] = NPOP
+    lab_options["eval_cache"] = {"max_entries": 10000, "quantum": 1e-9}
+    lab = genalgo.create_lab_test(json.dumps(lab_options))
+    lab.register_dataset("rows", [[i / 10] for i in range(10)])
+    algo_a_ind = lab.register_algo_A()
+    lab.configure_algo(algo_a_ind, genalgo.get_algo_default())
+    run_lab(lab)
+    print("Cache statistics: {}".format(lab.get_cache_stats()))
+
+def test_diversity():
+    lab = create_lab_object()
+    algo_a_ind = lab.register_algo_A()
+    algo_config = json.loads(genalgo.get_algo_default())
+    algo_config["diversity"] = {"DuplicateElimination": {"quantum": 1e-6}}
+    lab.configure_algo(algo_a_ind, json.dumps(algo_config))
+    run_lab(lab)
+    diversity = json.loads(lab.get_diversity(algo_a_ind))
+    print("Mean distance: {}, gene entropy: {}".format(
+        diversity["mean_distance"], diversity["gene_entropy"]))
+
+def test_restarts():
+    lab_options = json.loads(genalgo.get_lab_default())
+    lab_options["npop"] = NPOP
+    lab_options["restart"] = {"policy": {"IPOP": {"factor": 2.0}}, "stagnation": 3,
+            "tolerance": 1e-3, "max_pop_factor": 4.0}
+    lab = genalgo.create_lab_test(json.dumps(lab_options))
+    lab.register_empty_dataset(1)
+    algo_a_ind = lab.register_algo_A()
+    lab.configure_algo(algo_a_ind, genalgo.get_algo_default())
+    run_lab(lab)
+    print("Restarts: {}".format(lab.get_restart_counts()))
+    for archived in json.loads(lab.get_restart_archive()):
+        print("Restart {} of algo {}: {}".format(archived["restart"], archived["algo"],
+            archived["cell"]["score"]))
+
+def test_hall_of_fame():
+    lab = create_lab_object()
+    labast = genalgo.create_labmap_assistant("RingFormat")
+    for i in range(0, 4, 2):
+        labast.add_opti_part("ISO" + chr(ord("A") + i), [i, i+1], 0.6, "Darwin", "")
+    lab.apply_map_with_algo_A(labast.generate_map("Darwin"))
+    lab.set_output_algorithm(labast.get_output_index())
+    run_lab(lab)
+    for entry in json.loads(lab.get_hall_of_fame()):
+        print("Algo {}, generation {}: {}".format(entry["algo"], entry["generation"],
+            entry["cell"]["score"]))
+    export = json.loads(lab.export_lab())
+    print("Exported {} algos, {} cells in hall of fame".format(len(export["map"]),
+        len(export["hall_of_fame"])))
+
+def test_genome_migration():
+    lab = create_lab_object()
+    lab.apply_map_with_algo_A(json.dumps([json.loads(genalgo.get_algo_default())]))
+    run_lab(lab)
+    export = json.loads(lab.export_lab())
+    # Pretend the cells were saved when the genome had no 3rd parameter
+    for entry in export["hall_of_fame"]:
+        del entry["cell"]["genome"][2]
+        entry["cell"]["version"] = 0
+    restored = create_lab_object()
+    restored.register_genome_adapter(0, json.dumps({"AddGenes": {"at": 2, "values": [0.5]}}))
+    restored.import_lab_with_algo_A(json.dumps(export))
+    print("Restored hall of fame: {}".format(json.loads(restored.get_hall_of_fame())[0]["cell"]))
+    run_lab(restored)
+
+def test_seed_genomes():
+    seeds = [[0.5, 0.5, 0.5, 0.45], {"genome": [0.4, 0.5, 0.6, 0.5], "version": 1}]
+    with open("/tmp/petridish_seeds.json", "w") as f:
+        json.dump(seeds, f)
+    lab = create_lab_object()
+    lab.apply_map_with_algo_A(json.dumps([json.loads(genalgo.get_algo_default())]))
+    lab.import_seeds_from_file("/tmp/petridish_seeds.json", json.dumps("LatinHypercube"), None)
+    run_lab(lab)
+    lab.seed_from_hall_of_fame(json.dumps({"Mutants": {"sigma": 0.05}}), 0)
+    run_lab(lab)
+
+def test_init_strategies():
+    for strategy in ["Uniform", "LatinHypercube", "Sobol", "Halton", "Opposition"]:
+        algo = json.loads(genalgo.get_algo_default())
+        algo["init"] = strategy
+        lab = create_lab_object()
+        lab.apply_map_with_algo_A(json.dumps([algo]))
+        print(strategy)
+        run_lab(lab)
+
+def test_symbolic_regression():
+    lab_options = json.loads(genalgo.get_lab_default())
+    lab_options["npop"] = NPOP
+    lab = genalgo.create_lab_regression(json.dumps(lab_options))
+    rows = [[x / 4, y / 4, (x / 4) * (x / 4) + (y / 4)] for x in range(-4, 5) for y in range(-4, 5)]
+    lab.register_dataset("rows", rows)
+    algo_ind = lab.register_algo_sr()
+    algo_config = json.loads(genalgo.get_algo_default())
+    algo_config["method"] = "GeneticProgramming"
+    algo_config["method_options"] = {"GeneticProgrammingConfig": {
+        "crossover_proba": 0.8, "point_mutation_proba": 0.1, "subtree_mutation_proba": 0.05,
+        "tournament_size": 5, "init_min_depth": 2, "init_max_depth": 5,
+        "mutation_max_depth": 3, "max_depth": 10}}
+    lab.configure_algo(algo_ind, json.dumps(algo_config))
+    for i in range(NITER):
+        g, s = lab.start(NGENS)
+        pred = lab.get_special_data(algo_ind, json.dumps({"method": "predict", "genome": g, "inputs": [0.5, 0.25]}))
+        print("Mean squared error {}, f(0.5, 0.25) = {} (expected 0.5)".format(s, json.loads(pred)["result"]))
+
+def test_mlp_xor():
+    lab_options = json.loads(genalgo.get_lab_default())
+    lab_options["npop"] = NPOP
+    lab = genalgo.create_lab_xor(json.dumps(lab_options))
+    lab.register_dataset("xor", [[0, 0, 0], [0, 1, 1], [1, 0, 1], [1, 1, 0]])
+    algo_ind = lab.register_algo_mlp()
+    lab.configure_algo(algo_ind, genalgo.get_algo_default())
+    for i in range(NITER):
+        g, s = lab.start(NGENS)
+        outputs = [json.loads(lab.get_special_data(algo_ind, json.dumps(
+            {"method": "predict", "genome": g, "inputs": inputs})))["result"][0]
+            for inputs in [[0, 0], [0, 1], [1, 0], [1, 1]]]
+        print("Cross entropy {}, outputs {}".format(s, ["{:.3f}".format(o) for o in outputs]))
+
 ALL_TESTS = [
         test_empty_opti,
+        test_minibatch_dataset,
+        test_validation_dataset,
+        test_python_dataset,
+        test_eval_cache,
+        test_diversity,
+        test_restarts,
+        test_hall_of_fame,
+        test_genome_migration,
+        test_seed_genomes,
+        test_init_strategies,
+        test_symbolic_regression,
+        test_mlp_xor,
 
         # LAB MAPS
         test_map_assistant,
         test_apply_map,
         test_create_from_map,
+        test_map_assistant_options,
+        test_toml_map,
+        test_sensitivity_map,
         ]
 
 if __name__ == "__main__":
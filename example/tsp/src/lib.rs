@@ -0,0 +1,151 @@
+use petridish::generate_py_ifaces;
+use petridish::cell::*;
+use petridish::lab::Lab;
+use petridish::dataset::GenalgoData;
+use petridish::permutation::{self, Permutation};
+
+use serde_json::json;
+
+const NCITIES: usize = 20;
+
+type City = (f64, f64);
+
+/*  Default cities, on a unit circle but numbered in a scrambled order so the identity tour is
+ *  far from the optimal one, the perimeter of the polygon */
+fn circle_cities() -> Vec<City> {
+    (0..NCITIES).map(|n| {
+        let angle = 2.0 * std::f64::consts::PI * (((n * 7) % NCITIES) as f64) / (NCITIES as f64);
+        (angle.cos(), angle.sin())
+    }).collect()
+}
+
+fn tour_length(tour: &Permutation, cities: &Vec<City>) -> f64 {
+    (0..tour.len()).map(|n| {
+        let (a, b) = (cities[tour[n]], cities[tour[(n + 1) % tour.len()]]);
+        ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+    }).sum()
+}
+
+#[derive(Clone)]
+pub struct TspAlgo{
+    cities: Vec<City>,
+    circle: bool,
+}
+
+impl Algo for TspAlgo{
+    type CellType = TspCell;
+
+    fn new() -> Self where Self: Sized{
+        TspAlgo { cities: circle_cities(), circle: true }
+    }
+
+    fn reset(&mut self){
+    }
+
+    fn send_special_data(&self, params: &serde_json::Value) -> Result<JsonData, JsonData>{
+        match params.get("method").and_then(|m| m.as_str()) {
+            Some("expected_optimum") if self.circle => Ok(json!({
+                "result": 2.0 * (NCITIES as f64) * (std::f64::consts::PI / (NCITIES as f64)).sin()
+            }).to_string()),
+            Some("expected_optimum") => Err("Optimum only known for the default cities".to_string()),
+            Some("tour") => {
+                let genome: Genome = match serde_json::from_value(params["genome"].clone()) {
+                    Ok(g) => g,
+                    Err(e) => return Err(e.to_string()),
+                };
+                let tour = permutation::from_genome(&genome);
+                Ok(json!({"length": tour_length(&tour, &self.cities), "tour": tour}).to_string())
+            }
+            _ => Err("Please specify method: expected_optimum or tour".to_string()),
+        }
+    }
+
+    fn recv_special_data(&mut self, data: &serde_json::Value) -> Result<(), JsonData>{
+        if let Some(cities) = data.get("cities") {
+            let cities: Vec<City> = match serde_json::from_value(cities.clone()) {
+                Ok(c) => c,
+                Err(e) => return Err(e.to_string()),
+            };
+            if cities.len() != NCITIES {
+                return Err(format!("Expected {} cities, got {}", NCITIES, cities.len()));
+            }
+            self.cities = cities;
+            self.circle = false;
+        }
+        Ok(())
+    }
+
+    // Genomes as the list of the cities visited
+    fn genome_from_json(&self, jsdata: JsonData) -> Result<Genome, Errcode>{
+        let tour: Permutation = serde_json::from_str(&jsdata)?;
+        if !permutation::is_permutation(&tour, NCITIES) {
+            return Err(Errcode::ValidationError("tour is not a permutation of the cities"));
+        }
+        Ok(permutation::to_genome(&tour))
+    }
+
+    fn genome_to_json(&self, genome: Genome) -> Result<JsonData, Errcode>{
+        Ok(serde_json::to_string(&permutation::from_genome(&genome))?)
+    }
+
+    fn create_cell_from_genome(&self, genome: &Genome) -> Self::CellType{
+        TspCell {
+            celldata: CellData { genome: genome.clone(), score: 0.0, version: TspCell::genome_version() },
+            tour: permutation::from_genome(genome),
+            cities: self.cities.clone(),
+        }
+    }
+
+    fn check_generation_over(&self, _genalgo: &Lab<TspCell>) -> bool{
+        true
+    }
+
+    fn initialize_cells(&mut self, pop: &mut Vec<Self::CellType>){
+        for cell in pop.iter_mut(){
+            cell.cities = self.cities.clone();
+        }
+    }
+
+    fn process_data(&mut self, pop: &mut Vec<Self::CellType>, data: &GenalgoData){
+        for cell in pop.iter_mut(){
+            cell.action(data);
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct TspCell{
+    celldata: CellData,
+    tour: Permutation,
+    cities: Vec<City>,
+}
+
+impl Cell for TspCell{
+    type Data = GenalgoData;
+
+    fn get_genome_length() -> usize{
+        NCITIES
+    }
+
+    fn genome_version_adapt(genome: &Genome, _version: u64) -> Genome{
+        genome.clone()
+    }
+
+    fn get_data(&self) -> &CellData{
+        &self.celldata
+    }
+
+    fn action(&mut self, _data: &GenalgoData){
+        self.celldata.score = tour_length(&self.tour, &self.cities);
+    }
+
+    fn reset(&mut self, genome: &Genome){
+        self.celldata.score = 0.0;
+        self.celldata.genome = genome.clone();
+        self.tour = permutation::from_genome(genome);
+    }
+}
+
+generate_py_ifaces!(petridish,
+    [tsp] TspCell => (tsp => TspAlgo),
+);
@@ -0,0 +1,39 @@
+#!/usr/bin/env python3
+#-*-encoding:utf-8*-
+
+# Build with "cargo build --release" and link target/release/libtsp.so to ./genalgo.so
+
+import genalgo
+import json
+import sys
+
+NPOP = 1000
+NGENS = 200
+UPDATE_GEN_NB = 20
+
+lab_options = json.loads(genalgo.get_lab_default())
+lab_options["npop"] = NPOP
+lab = genalgo.create_lab_tsp(json.dumps(lab_options))
+algo_ind = lab.register_algo_tsp()
+
+algo_config = json.loads(genalgo.get_algo_default())
+algo_config["method"] = "Permutation"
+algo_config["method_options"] = {"PermutationConfig": {
+    "crossover": sys.argv[1] if len(sys.argv) > 1 else "Order",
+    "mutation": "Inversion",
+    "crossover_proba": 0.9,
+    "mutation_proba": 0.3,
+    "tournament_size": 3,
+}}
+lab.configure_algo(algo_ind, json.dumps(algo_config))
+lab.register_empty_dataset(1)
+
+optimum = json.loads(lab.get_special_data(algo_ind, json.dumps({"method": "expected_optimum"})))["result"]
+print("Optimal tour length: {:.4f}".format(optimum))
+
+for gen in range(UPDATE_GEN_NB, NGENS + 1, UPDATE_GEN_NB):
+    genome, score = lab.start(UPDATE_GEN_NB)
+    print("Generation {}:\tbest tour length {:.4f}".format(gen, score))
+
+tour = json.loads(lab.get_special_data(algo_ind, json.dumps({"method": "tour", "genome": genome})))
+print("Best tour:", tour["tour"])
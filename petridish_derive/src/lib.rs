@@ -0,0 +1,117 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Expr, Fields, Type};
+
+enum FieldKind {
+    Float,
+    Integer,
+    Bool,
+}
+
+fn field_kind(ty: &Type) -> syn::Result<FieldKind> {
+    let name = match ty {
+        Type::Path(p) => p.path.segments.last().map(|s| s.ident.to_string()).unwrap_or_default(),
+        _ => String::new(),
+    };
+    match name.as_str() {
+        "f32" | "f64" => Ok(FieldKind::Float),
+        "bool" => Ok(FieldKind::Bool),
+        "i8" | "i16" | "i32" | "i64" | "i128" | "isize"
+            | "u8" | "u16" | "u32" | "u64" | "u128" | "usize" => Ok(FieldKind::Integer),
+        _ => Err(syn::Error::new_spanned(ty, "unsupported genome field type, expected a float, an integer or bool")),
+    }
+}
+
+// Bounds given by #[genome(min = .., max = ..)], [0, 1] by default
+fn field_bounds(field: &syn::Field) -> syn::Result<(TokenStream2, TokenStream2)> {
+    let mut min = quote!(0);
+    let mut max = quote!(1);
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("genome")) {
+        attr.parse_nested_meta(|meta| {
+            let value: Expr = meta.value()?.parse()?;
+            if meta.path.is_ident("min") {
+                min = quote!(#value);
+            } else if meta.path.is_ident("max") {
+                max = quote!(#value);
+            } else {
+                return Err(meta.error("expected `min` or `max`"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok((min, max))
+}
+
+/*  Implements petridish::genome::GenomeParams for a struct of named numeric fields,
+ *  each field being encoded by one gene, scaled to the bounds of the field.
+ *  Fields of any other type (String, arrays, Option, ...) are a compile error. */
+#[proc_macro_derive(Genome, attributes(genome))]
+pub fn derive_genome(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match impl_genome_params(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn impl_genome_params(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(s) => match &s.fields {
+            Fields::Named(f) => &f.named,
+            _ => return Err(syn::Error::new_spanned(input, "Genome derive needs named fields")),
+        },
+        _ => return Err(syn::Error::new_spanned(input, "Genome derive only applies to structs")),
+    };
+
+    let mut keys = vec![];
+    let mut bounds = vec![];
+    let mut from_values = vec![];
+    let mut to_values = vec![];
+    let mut kinds = vec![];
+    for (n, field) in fields.iter().enumerate() {
+        let ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let (min_expr, max_expr) = field_bounds(field)?;
+        let (min, max) = (quote!(((#min_expr) as f64)), quote!(((#max_expr) as f64)));
+        keys.push(ident.to_string());
+        bounds.push(quote!((#min, #max)));
+        match field_kind(ty)? {
+            FieldKind::Float => {
+                from_values.push(quote!(#ident: values[#n] as #ty));
+                to_values.push(quote!(self.#ident as f64));
+                kinds.push(quote!(::petridish::discrete::GeneKind::Real));
+            }
+            FieldKind::Integer => {
+                from_values.push(quote!(#ident: values[#n].round() as #ty));
+                to_values.push(quote!(self.#ident as f64));
+                kinds.push(quote!(
+                    ::petridish::discrete::GeneKind::Integer((#min_expr) as i64, (#max_expr) as i64)
+                ));
+            }
+            FieldKind::Bool => {
+                kinds.push(quote!(::petridish::discrete::GeneKind::Bit));
+                from_values.push(quote!(#ident: values[#n] >= ((#min) + (#max)) / 2.0));
+                to_values.push(quote!(if self.#ident { #max } else { #min }));
+            }
+        }
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::petridish::genome::GenomeParams for #name #ty_generics #where_clause {
+            const KEYS: &'static [&'static str] = &[#(#keys),*];
+            const BOUNDS: &'static [(f64, f64)] = &[#(#bounds),*];
+            const KINDS: &'static [::petridish::discrete::GeneKind] = &[#(#kinds),*];
+
+            fn from_values(values: &[f64]) -> Self {
+                #name { #(#from_values),* }
+            }
+
+            fn to_values(&self) -> Vec<f64> {
+                vec![#(#to_values),*]
+            }
+        }
+    })
+}
@@ -1,8 +1,10 @@
-use crate::cell::{Cell, CellData, Genome};
-use crate::dataset::GenalgoData;
+use crate::cell::{Cell, CellData, Genome, Score};
+use crate::dataset::DatasetHandler;
+use crate::diversity::DiversityPreservation;
 use crate::errors::Errcode;
 use crate::genalgomethods::{GenalgoMethodsAvailable, GenalgoMethodsConfigurations};
 use crate::lab::Lab;
+use crate::sampling::InitStrategy;
 use crate::utils::JsonData;
 
 use serde::{Deserialize, Serialize};
@@ -13,8 +15,8 @@ pub trait Algo {
     where
         Self: Sized;
 
-    fn genome_from_json(&self, jsdata: JsonData) -> Genome;
-    fn genome_to_json(&self, genome: Genome) -> JsonData;
+    fn genome_from_json(&self, jsdata: JsonData) -> Result<Genome, Errcode>;
+    fn genome_to_json(&self, genome: Genome) -> Result<JsonData, Errcode>;
 
     fn initialize_cells(&mut self, pop: &mut Vec<Self::CellType>);
     fn create_cell_from_genome(&self, genome: &Genome) -> Self::CellType;
@@ -22,11 +24,32 @@ pub trait Algo {
     fn recv_special_data(&mut self, data: &serde_json::Value) -> Result<(), JsonData>;
     fn send_special_data(&self, params: &serde_json::Value) -> Result<JsonData, JsonData>;
 
-    fn process_data(&mut self, pop: &mut Vec<Self::CellType>, data: &GenalgoData);
+    fn process_data(&mut self, pop: &mut Vec<Self::CellType>, data: &<Self::CellType as Cell>::Data);
     fn check_generation_over(&self, genalgo: &Lab<Self::CellType>) -> bool;
     fn reset(&mut self);
 }
 
+// Score genomes outside of any lab population, by feeding them all the datasets entirely
+pub fn evaluate_genomes<T: Cell>(
+    algo: &mut dyn Algo<CellType = T>,
+    genomes: &[Genome],
+    datasets: &mut [Box<dyn DatasetHandler<T::Data>>],
+) -> Vec<CellData> {
+    let mut cells: Vec<T> = genomes
+        .iter()
+        .map(|g| algo.create_cell_from_genome(g))
+        .collect();
+    algo.initialize_cells(&mut cells);
+    for dataset in datasets.iter_mut() {
+        dataset.prepare_full();
+        while let Some(data) = dataset.get_next_data() {
+            algo.process_data(&mut cells, &data);
+        }
+    }
+    algo.reset();
+    cells.iter().map(|c| c.get_data().clone()).collect()
+}
+
 pub type AlgoID = usize;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -43,18 +66,14 @@ pub struct AlgoConfiguration {
     pub give: Vec<AlgoID>,              // Algos to give best cell
     pub impr_genes: Option<Vec<usize>>, // Index of genes to improve
     pub population: AlgoPopulation,
+    #[serde(default)]
+    pub diversity: DiversityPreservation,
+    #[serde(default)]
+    pub init: InitStrategy,
     //TODO  IMPORTANT   Add Vec of objectives (score_index, maximize / minimize)
 }
 
 impl AlgoConfiguration {
-    pub fn get_pop_and_elite(&self, pop: usize, elite_ratio: f64) -> (usize, usize) {
-        let algopop = match self.population{
-                AlgoPopulation::WeightofTot(w) => ((pop as f64) * w) as usize,
-                AlgoPopulation::FixedSize(s) => s,
-        };
-        (algopop, ((algopop as f64) * elite_ratio) as usize)
-    }
-
     pub fn default() -> AlgoConfiguration {
         AlgoConfiguration {
             id: "Default".to_string(),
@@ -63,6 +82,8 @@ impl AlgoConfiguration {
             give: vec![],
             impr_genes: None,
             population: AlgoPopulation::WeightofTot(1.0),
+            diversity: DiversityPreservation::default(),
+            init: InitStrategy::default(),
         }
     }
 
@@ -78,6 +99,8 @@ impl AlgoConfiguration {
             method: method,
             impr_genes: None,
             population: AlgoPopulation::WeightofTot(1.0),
+            diversity: DiversityPreservation::default(),
+            init: InitStrategy::default(),
         })
     }
 
@@ -114,6 +137,15 @@ impl AlgoResult {
         );
     }
 
+    // Cells which weren't evaluated, their scores are known from previous generations
+    pub fn load_cached_cells<T: Cell>(&mut self, cells: &[T], scores: &[Score]) {
+        for (cell, score) in cells.iter().zip(scores.iter()) {
+            let mut data = cell.get_data().clone();
+            data.score = *score;
+            self.cells_data.push(data);
+        }
+    }
+
     pub fn sort_cells(&mut self, maximize: bool) -> Result<(), Errcode> {
         if maximize {
             self.cells_data
@@ -134,6 +166,25 @@ impl AlgoResult {
         res
     }
 
+    pub fn get_top_genomes(&self) -> Vec<Genome> {
+        self.cells_data.iter().take(self.nelite).map(|c| c.genome.clone()).collect()
+    }
+
+    // Elites ranked among themselves by their scores on the full datasets,
+    // they keep their sampled scores so that they still compare with the other cells ones
+    pub fn rank_top_cells(&mut self, full_scores: &[Score], maximize: bool) {
+        let mut order: Vec<usize> = (0..full_scores.len().min(self.nelite)).collect();
+        if maximize {
+            order.sort_by(|a, b| full_scores[*b].partial_cmp(&full_scores[*a]).unwrap());
+        } else {
+            order.sort_by(|a, b| full_scores[*a].partial_cmp(&full_scores[*b]).unwrap());
+        }
+        let top: Vec<CellData> = order.iter().map(|i| self.cells_data[*i].clone()).collect();
+        for (n, cell) in top.into_iter().enumerate() {
+            self.cells_data[n] = cell;
+        }
+    }
+
     pub fn clone_top_cells(&self) -> Vec<CellData> {
         let mut res = vec![];
         for i in 0..self.nelite {
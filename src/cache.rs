@@ -0,0 +1,143 @@
+use crate::algo::AlgoID;
+use crate::cell::{Genome, Score};
+use crate::errors::Errcode;
+use crate::utils::JsonData;
+
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct EvalCacheConfig {
+    pub max_entries: usize,
+    pub quantum: f64,       // Genes closer than this are considered equal
+}
+
+impl Default for EvalCacheConfig {
+    fn default() -> EvalCacheConfig {
+        EvalCacheConfig {
+            max_entries: 100000,
+            quantum: 1e-9,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default)]
+pub struct EvalCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    pub entries: usize,
+}
+
+impl EvalCacheStats {
+    pub fn hit_rate(&self) -> f64 {
+        if self.hits + self.misses == 0 {
+            0.0
+        } else {
+            (self.hits as f64) / ((self.hits + self.misses) as f64)
+        }
+    }
+
+    pub fn to_json(&self) -> Result<JsonData, Errcode> {
+        Ok(serde_json::to_string(self)?)
+    }
+}
+
+type CacheKey = (AlgoID, Vec<i64>, u64);
+
+/*  Scores of the genomes already evaluated by the same algo on the same datasets, only valid
+ *  if the fitness is deterministic. When full, the oldest entries are evicted first. */
+pub struct EvalCache {
+    config: EvalCacheConfig,
+    scores: HashMap<CacheKey, Score>,
+    order: VecDeque<CacheKey>,
+    datasets: u64,
+    stats: EvalCacheStats,
+}
+
+impl EvalCache {
+    pub fn new(config: EvalCacheConfig) -> Result<EvalCache, Errcode> {
+        if config.max_entries == 0 {
+            return Err(Errcode::ValidationError("eval cache: max_entries == 0"));
+        }
+        if config.quantum <= 0.0 {
+            return Err(Errcode::ValidationError("eval cache: quantum <= 0"));
+        }
+        Ok(EvalCache {
+            config,
+            scores: HashMap::new(),
+            order: VecDeque::new(),
+            datasets: 0,
+            stats: EvalCacheStats::default(),
+        })
+    }
+
+    // Scores obtained on other datasets are never reused, the lab clears the cache
+    // whenever a registered dataset changes under the same id
+    pub fn set_datasets(&mut self, ids: &[String]) {
+        let mut hasher = DefaultHasher::new();
+        ids.hash(&mut hasher);
+        self.datasets = hasher.finish();
+    }
+
+    pub fn get(&mut self, algo: AlgoID, genome: &Genome) -> Option<Score> {
+        let res = self.scores.get(&self.key(algo, genome)).cloned();
+        if res.is_some() {
+            self.stats.hits += 1;
+        } else {
+            self.stats.misses += 1;
+        }
+        res
+    }
+
+    pub fn insert(&mut self, algo: AlgoID, genome: &Genome, score: Score) {
+        let key = self.key(algo, genome);
+        if self.scores.insert(key.clone(), score).is_some() {
+            return;
+        }
+        self.order.push_back(key);
+        while self.order.len() > self.config.max_entries {
+            if let Some(old) = self.order.pop_front() {
+                self.scores.remove(&old);
+                self.stats.evictions += 1;
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.scores.clear();
+        self.order.clear();
+    }
+
+    pub fn get_stats(&self) -> EvalCacheStats {
+        EvalCacheStats {
+            entries: self.scores.len(),
+            ..self.stats
+        }
+    }
+
+    fn key(&self, algo: AlgoID, genome: &Genome) -> CacheKey {
+        let genes = genome.iter().map(|g| (g / self.config.quantum).round() as i64).collect();
+        (algo, genes, self.datasets)
+    }
+}
+
+#[test]
+fn test_eval_cache() {
+    let mut cache = EvalCache::new(EvalCacheConfig { max_entries: 2, quantum: 1e-6 }).unwrap();
+    cache.insert(0, &vec![0.1, 0.2], 1.0);
+    assert_eq!(cache.get(0, &vec![0.1 + 1e-9, 0.2]), Some(1.0));
+    assert_eq!(cache.get(0, &vec![0.1, 0.3]), None);
+    assert_eq!(cache.get(1, &vec![0.1, 0.2]), None);
+    cache.insert(0, &vec![0.1, 0.3], 2.0);
+    cache.insert(0, &vec![0.1, 0.4], 3.0);
+    assert_eq!(cache.get(0, &vec![0.1, 0.2]), None);
+    cache.set_datasets(&["train".to_string()]);
+    assert_eq!(cache.get(0, &vec![0.1, 0.4]), None);
+
+    let stats = cache.get_stats();
+    assert_eq!((stats.hits, stats.misses, stats.evictions, stats.entries), (1, 4, 1, 2));
+    assert!((stats.hit_rate() - 0.2).abs() < 1e-12);
+}
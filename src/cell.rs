@@ -1,9 +1,10 @@
-use crate::dataset::GenalgoData;
+use crate::discrete::GeneKind;
+use crate::gp::PrimitiveSet;
+use crate::errors::Errcode;
 use crate::utils::JsonData;
 
 use rand::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 
 pub type Genome = Vec<f64>;
 
@@ -16,27 +17,114 @@ pub fn random_genome(ngens: usize) -> Genome {
     res
 }
 
-pub fn __genome_from_json(jsdata: JsonData, key_list: &Vec<&str>) -> Genome {
-    let data: serde_json::Value =
-        serde_json::from_str(jsdata.as_str()).expect("Error while parsing json");
+// Step of a genome key path: "layer.weights[2]" reaches the 3rd item of the "weights" array of "layer"
+enum KeyStep {
+    Field(String),
+    Index(usize),
+}
+
+fn parse_key_path(key: &str) -> Result<Vec<KeyStep>, Errcode> {
+    let mut res = vec![];
+    for part in key.split('.') {
+        let (field, indexes) = match part.find('[') {
+            Some(n) => part.split_at(n),
+            None => (part, ""),
+        };
+        if field.is_empty() && (res.is_empty() || indexes.is_empty()) {
+            return Err(Errcode::GenomeKeyInvalid(key.to_string()));
+        }
+        if !field.is_empty() {
+            res.push(KeyStep::Field(field.to_string()));
+        }
+        let mut rest = indexes;
+        while !rest.is_empty() {
+            let end = match (rest.starts_with('['), rest.find(']')) {
+                (true, Some(end)) => end,
+                _ => return Err(Errcode::GenomeKeyInvalid(key.to_string())),
+            };
+            match rest[1..end].parse::<usize>() {
+                Ok(ind) => res.push(KeyStep::Index(ind)),
+                Err(_) => return Err(Errcode::GenomeKeyInvalid(key.to_string())),
+            }
+            rest = &rest[(end + 1)..];
+        }
+    }
+    Ok(res)
+}
+
+fn get_at_path<'a>(data: &'a serde_json::Value, key: &str) -> Result<&'a serde_json::Value, Errcode> {
+    let mut val = data;
+    for step in parse_key_path(key)? {
+        let next = match step {
+            KeyStep::Field(f) => val.get(f.as_str()),
+            KeyStep::Index(i) => val.get(i),
+        };
+        val = match next {
+            Some(v) => v,
+            None => return Err(Errcode::GenomeKeyDoesntExist(key.to_string())),
+        };
+    }
+    Ok(val)
+}
+
+// Objects and arrays on the path are created, missing array items are set to null
+fn set_at_path(data: &mut serde_json::Value, key: &str, gene: f64) -> Result<(), Errcode> {
+    let mut val = data;
+    for step in parse_key_path(key)? {
+        val = match step {
+            KeyStep::Field(f) => {
+                if val.is_null() {
+                    *val = serde_json::Value::Object(serde_json::Map::new());
+                }
+                match val.as_object_mut() {
+                    Some(obj) => obj.entry(f).or_insert(serde_json::Value::Null),
+                    None => return Err(Errcode::GenomeKeyInvalid(key.to_string())),
+                }
+            }
+            KeyStep::Index(i) => {
+                if val.is_null() {
+                    *val = serde_json::Value::Array(vec![]);
+                }
+                match val.as_array_mut() {
+                    Some(arr) => {
+                        if arr.len() <= i {
+                            arr.resize(i + 1, serde_json::Value::Null);
+                        }
+                        &mut arr[i]
+                    }
+                    None => return Err(Errcode::GenomeKeyInvalid(key.to_string())),
+                }
+            }
+        };
+    }
+    if !val.is_null() {
+        return Err(Errcode::GenomeKeyInvalid(key.to_string()));
+    }
+    *val = serde_json::json!(gene);
+    Ok(())
+}
+
+pub fn __genome_from_json(jsdata: JsonData, key_list: &[&str]) -> Result<Genome, Errcode> {
+    let data: serde_json::Value = serde_json::from_str(jsdata.as_str())?;
     let mut res = Genome::new();
     for key in key_list.iter() {
-        res.push(
-            data[key]
-                .as_f64()
-                .expect("Error while decoding genome, cannot convert as floating number"),
-        );
+        match get_at_path(&data, key)?.as_f64() {
+            Some(gene) => res.push(gene),
+            None => return Err(Errcode::GenomeKeyNotNumeric(key.to_string())),
+        }
     }
-    res
+    Ok(res)
 }
 
-pub fn __genome_to_json(genome: Genome, key_list: &Vec<&str>) -> JsonData {
-    assert_eq!(genome.len(), key_list.len());
-    let mut result: HashMap<&str, f64> = HashMap::new();
-    for (nb, key) in key_list.into_iter().enumerate() {
-        result.insert(key, genome[nb]);
+pub fn __genome_to_json(genome: Genome, key_list: &[&str]) -> Result<JsonData, Errcode> {
+    if genome.len() != key_list.len() {
+        return Err(Errcode::SizeError("genome", key_list.len(), genome.len()));
     }
-    serde_json::to_string(&result).expect("Cannot serialize genome to Json")
+    let mut result = serde_json::Value::Null;
+    for (nb, key) in key_list.iter().enumerate() {
+        set_at_path(&mut result, key, genome[nb])?;
+    }
+    Ok(serde_json::to_string(&result)?)
 }
 
 pub type Score = f64;
@@ -49,9 +137,98 @@ pub struct CellData {
 }
 
 pub trait Cell {
+    type Data;     // Items emitted by the datasets, GenalgoData for the Python bindings
+
     fn get_genome_length() -> usize;
+    // Min and max lengths for cells with variable-length genomes, fixed length if None.
+    // get_genome_length is then the length of the initial genomes
+    fn genome_length_bounds() -> Option<(usize, usize)> {
+        None
+    }
+    // Kind of value encoded by each gene (see discrete.rs), all real genes if empty
+    fn gene_kinds() -> Vec<GeneKind> {
+        vec![]
+    }
+    // Functions and terminals of the trees encoded by the genomes, for genetic programming
+    fn primitive_set() -> Option<PrimitiveSet> {
+        None
+    }
     fn get_data(&self) -> &CellData;
-    fn action(&mut self, data: &GenalgoData);
+    fn action(&mut self, data: &Self::Data);
     fn reset(&mut self, genome: &Genome);
+
+    // Version of the genome layout, genomes saved with older versions are migrated to it
+    fn genome_version() -> u64 {
+        0
+    }
+    // Adapts a genome of the given version to the following one
     fn genome_version_adapt(genome: &Genome, version: u64) -> Genome;
 }
+
+pub fn genome_length_is_valid<T: Cell>(len: usize) -> bool {
+    match T::genome_length_bounds() {
+        Some((min, max)) => (min..=max).contains(&len),
+        None => len == T::get_genome_length(),
+    }
+}
+
+// Longest genome a cell can have, used to size the samplings
+pub fn max_genome_length<T: Cell>() -> usize {
+    match T::genome_length_bounds() {
+        Some((_, max)) => max,
+        None => T::get_genome_length(),
+    }
+}
+
+/*  Dummy cell for the tests, its data is set by the algos of the tests:
+ *      test_cell!(Name, Data, length, bounds: (min, max), data_version: 1, adapt: |genome, version| ...)
+ *  the bounds, the version of the data and the genome adapter being optional.
+ *  Other Cell methods can be given in a block at the end of the arguments */
+#[cfg(test)]
+macro_rules! test_cell {
+    ($name:ident, $data:ty, $len:expr $(, bounds: $bounds:expr)? $(, data_version: $version:expr)?
+        $(, adapt: $adapt:expr)? $(, { $($extra:item)* })?) => {
+        struct $name {
+            data: $crate::cell::CellData,
+        }
+        impl $crate::cell::Cell for $name {
+            type Data = $data;
+            fn get_genome_length() -> usize { $len }
+            fn genome_length_bounds() -> Option<(usize, usize)> { $crate::cell::test_cell!(@or None $(, Some($bounds))?) }
+            fn get_data(&self) -> &$crate::cell::CellData { &self.data }
+            fn action(&mut self, _data: &$data) {}
+            fn reset(&mut self, genome: &$crate::cell::Genome) {
+                self.data = $crate::cell::CellData { genome: genome.clone(), score: 0.0,
+                    version: $crate::cell::test_cell!(@or 0 $(, $version)?) };
+            }
+            fn genome_version_adapt(genome: &$crate::cell::Genome, version: u64) -> $crate::cell::Genome {
+                $crate::cell::test_cell!(@or { let _ = version; genome.clone() } $(, ($adapt)(genome, version))?)
+            }
+            $($($extra)*)?
+        }
+    };
+    (@or $default:expr) => { $default };
+    (@or $default:expr, $value:expr) => { $value };
+}
+#[cfg(test)]
+pub(crate) use test_cell;
+
+#[test]
+fn test_genome_json_key_paths() {
+    let keys = ["speed", "layer.bias", "layer.weights[0]", "layer.weights[1]", "grid[1][0]"];
+    let json = __genome_to_json(vec![0.1, 0.2, 0.3, 0.4, 0.5], &keys).unwrap();
+    let data: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(data["layer"]["weights"], serde_json::json!([0.3, 0.4]));
+    assert_eq!(data["grid"], serde_json::json!([null, [0.5]]));
+    assert_eq!(__genome_from_json(json, &keys).unwrap(), vec![0.1, 0.2, 0.3, 0.4, 0.5]);
+
+    let missing = __genome_from_json("{\"speed\": 0.1, \"layer\": {}}".to_string(), &keys);
+    assert!(matches!(missing, Err(Errcode::GenomeKeyDoesntExist(k)) if k == "layer.bias"));
+    let not_numeric = __genome_from_json("{\"speed\": \"fast\"}".to_string(), &keys);
+    assert!(matches!(not_numeric, Err(Errcode::GenomeKeyNotNumeric(k)) if k == "speed"));
+    assert!(matches!(__genome_to_json(vec![0.1], &keys), Err(Errcode::SizeError(_, 5, 1))));
+    assert!(matches!(__genome_to_json(vec![0.1, 0.2], &["a", "a.b"]),
+        Err(Errcode::GenomeKeyInvalid(_))));
+    assert!(matches!(__genome_from_json("{}".to_string(), &["w[x]"]),
+        Err(Errcode::GenomeKeyInvalid(_))));
+}
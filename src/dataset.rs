@@ -1,35 +1,184 @@
+use crate::errors::Errcode;
+
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+use std::str::FromStr;
+use strum_macros::EnumString;
+
+mod csv;
+pub use self::csv::CsvDataset;
+mod jsonlines;
+pub use jsonlines::JsonLinesDataset;
+mod binary;
+pub use binary::BinaryDataset;
+mod sampling;
+pub use sampling::{MiniBatchDataset, ShuffledDataset};
+mod python;
+pub use python::PyDataset;
+
+// Default type of the data items emitted by the datasets
 pub type GenalgoData = Vec<f64>;
 
-pub trait DatasetHandler {
+// Training datasets score the cells, validation ones only the elites of the output algo
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize, EnumString)]
+pub enum DatasetRole {
+    Training,
+    Validation,
+}
+
+impl DatasetRole {
+    pub fn from_name(name: &str) -> Result<DatasetRole, Errcode> {
+        DatasetRole::from_str(name).map_err(|_| Errcode::DatasetRoleDoesntExist(name.to_string()))
+    }
+}
+
+pub trait DatasetHandler<D = GenalgoData> {
     fn prepare(&mut self);
-    fn get_next_data(&mut self) -> Option<GenalgoData>;
+    fn get_next_data(&mut self) -> Option<D>;
+
+    // Emits the whole dataset, even if only a sample of it is emitted after prepare()
+    fn prepare_full(&mut self) {
+        self.prepare();
+    }
+
+    // Elites are scored again on the whole datasets before the next generation is prepared
+    fn reevaluate_elites(&self) -> bool {
+        false
+    }
+
+    // Same items in the same order each generation, required to use the evaluation cache
+    fn is_deterministic(&self) -> bool {
+        true
+    }
 }
 
-pub struct EmptyDataset {
+pub struct EmptyDataset<D = GenalgoData> {
     pub nemission: usize,
     data_emitted: usize,
+    item: PhantomData<D>,
 }
 
-impl EmptyDataset {
-    pub fn new(nemission: usize) -> EmptyDataset {
+impl<D> EmptyDataset<D> {
+    pub fn new(nemission: usize) -> EmptyDataset<D> {
         EmptyDataset {
             nemission: nemission,
             data_emitted: 0,
+            item: PhantomData,
         }
     }
 }
 
-impl DatasetHandler for EmptyDataset {
+impl<D: Default> DatasetHandler<D> for EmptyDataset<D> {
     fn prepare(&mut self) {
         self.data_emitted = 0;
     }
 
-    fn get_next_data(&mut self) -> Option<GenalgoData> {
+    fn get_next_data(&mut self) -> Option<D> {
         if self.data_emitted < self.nemission {
             self.data_emitted += 1;
-            Option::Some(vec![])
+            Option::Some(D::default())
         } else {
             Option::None
         }
     }
 }
+
+// Rows kept in memory, emitted in order
+pub struct VecDataset<D = GenalgoData> {
+    rows: Vec<D>,
+    pos: usize,
+}
+
+impl<D> VecDataset<D> {
+    pub fn new(rows: Vec<D>) -> VecDataset<D> {
+        VecDataset { rows, pos: 0 }
+    }
+}
+
+impl<D: Clone> DatasetHandler<D> for VecDataset<D> {
+    fn prepare(&mut self) {
+        self.pos = 0;
+    }
+
+    fn get_next_data(&mut self) -> Option<D> {
+        let data = self.rows.get(self.pos).cloned();
+        self.pos += 1;
+        data
+    }
+}
+
+// Columns of the file datasets fed to the cells, all of them by default
+#[derive(Clone, Debug)]
+pub enum DatasetColumns {
+    All,
+    Indexes(Vec<usize>),
+    Names(Vec<String>),
+}
+
+impl DatasetColumns {
+    // Python side: no indexes and no names for all the columns
+    pub fn from_lists(indexes: Vec<usize>, names: Vec<String>) -> Result<DatasetColumns, Errcode> {
+        match (indexes.is_empty(), names.is_empty()) {
+            (true, true) => Ok(DatasetColumns::All),
+            (false, true) => Ok(DatasetColumns::Indexes(indexes)),
+            (true, false) => Ok(DatasetColumns::Names(names)),
+            (false, false) => Err(Errcode::ValidationError("dataset columns, both indexes and names set")),
+        }
+    }
+}
+
+fn select_columns(row: GenalgoData, columns: &Option<Vec<usize>>) -> Option<GenalgoData> {
+    match columns {
+        Some(ind) => ind.iter().map(|i| row.get(*i).cloned()).collect(),
+        None => Some(row),
+    }
+}
+
+fn names_to_indexes(names: &[String], header: &[String]) -> Result<Vec<usize>, Errcode> {
+    let mut res = vec![];
+    for name in names.iter() {
+        match header.iter().position(|h| h == name) {
+            Some(i) => res.push(i),
+            None => return Err(Errcode::ColumnDoesntExist(name.clone())),
+        }
+    }
+    Ok(res)
+}
+
+#[test]
+fn test_file_datasets() {
+    fn read_all(dataset: &mut dyn DatasetHandler) -> Vec<GenalgoData> {
+        let mut res = vec![];
+        dataset.prepare();
+        while let Some(d) = dataset.get_next_data() {
+            res.push(d);
+        }
+        res
+    }
+    let dir = std::env::temp_dir();
+    let expected = vec![vec![3.0, 1.0], vec![6.0, 4.0]];
+
+    let csv_path = dir.join(format!("petridish_test_{}.csv", std::process::id()));
+    std::fs::write(&csv_path, "a;b;c\n1;2;3\n4;x;6\n\n4;5;6\n").unwrap();
+    let mut csv = CsvDataset::new(&csv_path, ';', true,
+        DatasetColumns::Names(vec!["c".to_string(), "a".to_string()])).unwrap();
+    assert_eq!(read_all(&mut csv), expected);
+    assert_eq!(read_all(&mut csv), expected);
+    assert!(CsvDataset::new(&csv_path, ';', true, DatasetColumns::Names(vec!["d".to_string()])).is_err());
+
+    let jsonl_path = dir.join(format!("petridish_test_{}.jsonl", std::process::id()));
+    std::fs::write(&jsonl_path, "{\"a\": 1, \"c\": 3}\n{\"a\": 4, \"c\": 6}\n").unwrap();
+    let mut jsonl = JsonLinesDataset::new(&jsonl_path,
+        DatasetColumns::Names(vec!["c".to_string(), "a".to_string()])).unwrap();
+    assert_eq!(read_all(&mut jsonl), expected);
+
+    let bin_path = dir.join(format!("petridish_test_{}.bin", std::process::id()));
+    BinaryDataset::write_rows(&bin_path, &[vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0]]).unwrap();
+    let mut bin = BinaryDataset::new(&bin_path, 3, DatasetColumns::Indexes(vec![2, 0])).unwrap();
+    assert_eq!(read_all(&mut bin), expected);
+    assert_eq!(read_all(&mut bin), expected);
+
+    for path in [csv_path, jsonl_path, bin_path].iter() {
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,74 @@
+use crate::dataset::*;
+
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+
+// Rows of ncols little-endian f64, without any header or separator
+pub struct BinaryDataset {
+    path: PathBuf,
+    ncols: usize,
+    columns: Option<Vec<usize>>,
+    reader: Option<BufReader<File>>,
+}
+
+impl BinaryDataset {
+    pub fn new<P: AsRef<Path>>(path: P, ncols: usize, columns: DatasetColumns
+        ) -> Result<BinaryDataset, Errcode> {
+        if ncols == 0 {
+            return Err(Errcode::ValidationError("binary dataset: ncols == 0"));
+        }
+        let columns = match columns {
+            DatasetColumns::All => None,
+            DatasetColumns::Indexes(ind) => {
+                if let Some(i) = ind.iter().find(|i| **i >= ncols) {
+                    return Err(Errcode::ColumnDoesntExist(i.to_string()));
+                }
+                Some(ind)
+            }
+            DatasetColumns::Names(_) => {
+                return Err(Errcode::ValidationError("binary dataset: columns have no name"))
+            }
+        };
+        File::open(&path)?;
+        Ok(BinaryDataset {
+            path: path.as_ref().to_path_buf(),
+            ncols,
+            columns,
+            reader: None,
+        })
+    }
+
+    pub fn write_rows<P: AsRef<Path>>(path: P, rows: &[GenalgoData]) -> Result<(), Errcode> {
+        let mut data = vec![];
+        for row in rows.iter() {
+            for val in row.iter() {
+                data.extend_from_slice(&val.to_le_bytes());
+            }
+        }
+        Ok(std::fs::write(path, data)?)
+    }
+}
+
+impl DatasetHandler for BinaryDataset {
+    fn prepare(&mut self) {
+        self.reader = match File::open(&self.path) {
+            Ok(f) => Some(BufReader::new(f)),
+            Err(e) => {
+                println!("Cannot open dataset {}: {}", self.path.display(), e);
+                None
+            }
+        };
+    }
+
+    fn get_next_data(&mut self) -> Option<GenalgoData> {
+        let mut buf = vec![0u8; self.ncols * 8];
+        // A truncated last row is ignored
+        self.reader.as_mut()?.read_exact(&mut buf).ok()?;
+        let row = buf
+            .chunks_exact(8)
+            .map(|b| f64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]))
+            .collect();
+        select_columns(row, &self.columns)
+    }
+}
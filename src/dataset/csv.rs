@@ -0,0 +1,87 @@
+use crate::dataset::*;
+
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+// Rows of numbers separated by a delimiter, with an optional header line
+pub struct CsvDataset {
+    path: PathBuf,
+    delimiter: char,
+    header: bool,
+    columns: Option<Vec<usize>>,
+    reader: Option<BufReader<File>>,
+    line_nb: usize,
+}
+
+impl CsvDataset {
+    pub fn new<P: AsRef<Path>>(path: P, delimiter: char, header: bool, columns: DatasetColumns
+        ) -> Result<CsvDataset, Errcode> {
+        let mut dataset = CsvDataset {
+            path: path.as_ref().to_path_buf(),
+            delimiter,
+            header,
+            columns: None,
+            reader: None,
+            line_nb: 0,
+        };
+        dataset.columns = match columns {
+            DatasetColumns::All => None,
+            DatasetColumns::Indexes(ind) => Some(ind),
+            DatasetColumns::Names(names) => {
+                if !header {
+                    return Err(Errcode::ValidationError("csv dataset: column names without header"));
+                }
+                let mut line = String::new();
+                BufReader::new(File::open(&dataset.path)?).read_line(&mut line)?;
+                let header_names: Vec<String> = dataset.split_line(&line).map(|f| f.to_string()).collect();
+                Some(names_to_indexes(&names, &header_names)?)
+            }
+        };
+        File::open(&dataset.path)?;
+        Ok(dataset)
+    }
+
+    fn split_line<'a>(&self, line: &'a str) -> impl Iterator<Item = &'a str> {
+        line.trim_end_matches(&['\r', '\n'][..])
+            .split(self.delimiter)
+            .map(|f| f.trim().trim_matches('"'))
+    }
+
+    fn parse_line(&self, line: &str) -> Option<GenalgoData> {
+        let row = self.split_line(line).map(|f| f.parse::<f64>()).collect::<Result<Vec<f64>, _>>();
+        select_columns(row.ok()?, &self.columns)
+    }
+}
+
+impl DatasetHandler for CsvDataset {
+    fn prepare(&mut self) {
+        self.line_nb = 0;
+        self.reader = match File::open(&self.path) {
+            Ok(f) => Some(BufReader::new(f)),
+            Err(e) => {
+                println!("Cannot open dataset {}: {}", self.path.display(), e);
+                None
+            }
+        };
+    }
+
+    fn get_next_data(&mut self) -> Option<GenalgoData> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            match self.reader.as_mut()?.read_line(&mut line) {
+                Ok(0) | Err(_) => return None,
+                Ok(_) => self.line_nb += 1,
+            }
+            if (self.header && self.line_nb == 1) || line.trim().is_empty() {
+                continue;
+            }
+            match self.parse_line(&line) {
+                Some(data) => return Some(data),
+                None => println!("Dataset {}: skipping malformed line {}",
+                    self.path.display(), self.line_nb),
+            }
+        }
+    }
+}
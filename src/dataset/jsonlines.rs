@@ -0,0 +1,77 @@
+use crate::dataset::*;
+
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+/*  One JSON value per line, either an array of numbers (columns selected by index),
+ *  or an object (columns selected by key, all the keys in alphabetical order by default) */
+pub struct JsonLinesDataset {
+    path: PathBuf,
+    columns: DatasetColumns,
+    reader: Option<BufReader<File>>,
+    line_nb: usize,
+}
+
+impl JsonLinesDataset {
+    pub fn new<P: AsRef<Path>>(path: P, columns: DatasetColumns) -> Result<JsonLinesDataset, Errcode> {
+        File::open(&path)?;
+        Ok(JsonLinesDataset {
+            path: path.as_ref().to_path_buf(),
+            columns,
+            reader: None,
+            line_nb: 0,
+        })
+    }
+
+    fn parse_line(&self, line: &str) -> Option<GenalgoData> {
+        match serde_json::from_str::<serde_json::Value>(line).ok()? {
+            serde_json::Value::Array(values) => {
+                let row = values.iter().map(|v| v.as_f64()).collect::<Option<Vec<f64>>>()?;
+                match &self.columns {
+                    DatasetColumns::All => Some(row),
+                    DatasetColumns::Indexes(ind) => select_columns(row, &Some(ind.clone())),
+                    DatasetColumns::Names(_) => None,
+                }
+            }
+            serde_json::Value::Object(obj) => match &self.columns {
+                DatasetColumns::All => obj.values().map(|v| v.as_f64()).collect(),
+                DatasetColumns::Names(keys) => keys.iter().map(|k| obj.get(k)?.as_f64()).collect(),
+                DatasetColumns::Indexes(_) => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+impl DatasetHandler for JsonLinesDataset {
+    fn prepare(&mut self) {
+        self.line_nb = 0;
+        self.reader = match File::open(&self.path) {
+            Ok(f) => Some(BufReader::new(f)),
+            Err(e) => {
+                println!("Cannot open dataset {}: {}", self.path.display(), e);
+                None
+            }
+        };
+    }
+
+    fn get_next_data(&mut self) -> Option<GenalgoData> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            match self.reader.as_mut()?.read_line(&mut line) {
+                Ok(0) | Err(_) => return None,
+                Ok(_) => self.line_nb += 1,
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+            match self.parse_line(&line) {
+                Some(data) => return Some(data),
+                None => println!("Dataset {}: skipping malformed line {}",
+                    self.path.display(), self.line_nb),
+            }
+        }
+    }
+}
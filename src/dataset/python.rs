@@ -0,0 +1,91 @@
+use crate::dataset::*;
+
+use pyo3::prelude::*;
+use pyo3::types::{PyIterator, PyList};
+use pyo3::AsPyPointer;
+
+/*  Dataset defined on the Python side: any iterable of rows (lists, tuples, 2D NumPy
+ *  arrays, ...), or a callable returning one, like a generator function.
+ *  The iterator is created again on each prepare(). Generator objects and other one-shot
+ *  iterators are read into a list once, on registration. */
+pub struct PyDataset {
+    source: PyObject,
+    iter: Option<PyObject>,
+    nrow: usize,
+}
+
+impl PyDataset {
+    pub fn new(py: Python, source: PyObject) -> PyResult<PyDataset> {
+        let obj = source.as_ref(py);
+        let source = if obj.is_callable() {
+            source
+        } else {
+            let iter = obj.iter()?;
+            if iter.as_ptr() == obj.as_ptr() {
+                PyList::new(py, iter.collect::<PyResult<Vec<&PyAny>>>()?).to_object(py)
+            } else {
+                source
+            }
+        };
+        Ok(PyDataset {
+            source,
+            iter: None,
+            nrow: 0,
+        })
+    }
+
+    fn create_iter(&self, py: Python) -> PyResult<PyObject> {
+        let source = self.source.as_ref(py);
+        let iterable = if source.is_callable() { source.call0()? } else { source };
+        Ok(iterable.iter()?.to_object(py))
+    }
+
+    fn extract_row(row: &PyAny) -> PyResult<GenalgoData> {
+        match row.extract::<GenalgoData>() {
+            Ok(data) => Ok(data),
+            Err(e) => {
+                if row.hasattr("tolist")? {
+                    row.call_method0("tolist")?.extract::<GenalgoData>()
+                } else {
+                    Err(e)
+                }
+            }
+        }
+    }
+}
+
+impl DatasetHandler for PyDataset {
+    fn prepare(&mut self) {
+        self.nrow = 0;
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        self.iter = match self.create_iter(py) {
+            Ok(iter) => Some(iter),
+            Err(e) => {
+                println!("Cannot iterate over the Python dataset: {}", e);
+                None
+            }
+        };
+    }
+
+    fn get_next_data(&mut self) -> Option<GenalgoData> {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let iter = PyIterator::from_object(py, self.iter.as_ref()?.as_ref(py)).ok()?;
+        for row in iter {
+            self.nrow += 1;
+            match row.and_then(PyDataset::extract_row) {
+                Ok(data) => return Some(data),
+                Err(e) => println!("Python dataset: skipping row {}: {}", self.nrow, e),
+            }
+        }
+        self.iter = None;
+        None
+    }
+
+    // A function may generate different items on each call
+    fn is_deterministic(&self) -> bool {
+        let gil = Python::acquire_gil();
+        !self.source.as_ref(gil.python()).is_callable()
+    }
+}
@@ -0,0 +1,212 @@
+use crate::dataset::*;
+
+use rand::prelude::*;
+use std::collections::BTreeMap;
+
+// Label of a data item, items with the same label are sampled in the same proportions
+pub type LabelFn<D> = Box<dyn Fn(&D) -> f64>;
+
+// Reads the whole wrapped dataset once, the first time it's needed
+fn load_rows<D>(inner: &mut Box<dyn DatasetHandler<D>>, rows: &mut Option<Vec<D>>) {
+    if rows.is_none() {
+        let mut res = vec![];
+        inner.prepare_full();
+        while let Some(data) = inner.get_next_data() {
+            res.push(data);
+        }
+        *rows = Some(res);
+    }
+}
+
+// Emits all the items of the wrapped dataset, in a new random order each generation
+pub struct ShuffledDataset<D = GenalgoData> {
+    inner: Box<dyn DatasetHandler<D>>,
+    rows: Option<Vec<D>>,
+    order: Vec<usize>,
+    pos: usize,
+}
+
+impl<D> ShuffledDataset<D> {
+    pub fn new(inner: Box<dyn DatasetHandler<D>>) -> ShuffledDataset<D> {
+        ShuffledDataset {
+            inner,
+            rows: None,
+            order: vec![],
+            pos: 0,
+        }
+    }
+}
+
+impl<D: Clone> DatasetHandler<D> for ShuffledDataset<D> {
+    fn prepare(&mut self) {
+        load_rows(&mut self.inner, &mut self.rows);
+        self.order = (0..self.rows.as_ref().unwrap().len()).collect();
+        self.order.shuffle(&mut rand::thread_rng());
+        self.pos = 0;
+    }
+
+    fn get_next_data(&mut self) -> Option<D> {
+        let ind = *self.order.get(self.pos)?;
+        self.pos += 1;
+        self.rows.as_ref()?.get(ind).cloned()
+    }
+
+    fn is_deterministic(&self) -> bool {
+        false
+    }
+}
+
+/*  Emits a random subset of batch_size items of the wrapped dataset each generation.
+ *  If a label column is set, each label keeps its share of the items in the batch.
+ *  The elites can be re-evaluated on the whole dataset before being selected. */
+pub struct MiniBatchDataset<D = GenalgoData> {
+    inner: Box<dyn DatasetHandler<D>>,
+    rows: Option<Vec<D>>,
+    batch_size: usize,
+    label: Option<LabelFn<D>>,
+    reevaluate_elites: bool,
+    order: Vec<usize>,
+    pos: usize,
+}
+
+impl MiniBatchDataset<GenalgoData> {
+    pub fn new(inner: Box<dyn DatasetHandler>, batch_size: usize, label_column: Option<usize>,
+        reevaluate_elites: bool) -> Result<MiniBatchDataset, Errcode> {
+        let label = label_column.map(|col| -> LabelFn<GenalgoData> {
+            Box::new(move |row: &GenalgoData| row.get(col).cloned().unwrap_or(f64::NAN))
+        });
+        MiniBatchDataset::with_label(inner, batch_size, label, reevaluate_elites)
+    }
+}
+
+impl<D> MiniBatchDataset<D> {
+    pub fn with_label(inner: Box<dyn DatasetHandler<D>>, batch_size: usize,
+        label: Option<LabelFn<D>>, reevaluate_elites: bool) -> Result<MiniBatchDataset<D>, Errcode> {
+        MiniBatchDataset::<D>::validate_batch_size(batch_size)?;
+        Ok(MiniBatchDataset {
+            inner,
+            rows: None,
+            batch_size,
+            label,
+            reevaluate_elites,
+            order: vec![],
+            pos: 0,
+        })
+    }
+
+    pub fn validate_batch_size(batch_size: usize) -> Result<(), Errcode> {
+        if batch_size == 0 {
+            return Err(Errcode::ValidationError("mini-batch dataset: batch_size == 0"));
+        }
+        Ok(())
+    }
+
+    fn draw_batch(&self, rng: &mut ThreadRng) -> Vec<usize> {
+        let rows = self.rows.as_ref().unwrap();
+        if self.batch_size >= rows.len() {
+            return (0..rows.len()).collect();
+        }
+        let label = match &self.label {
+            Some(label) => label,
+            None => return rand::seq::index::sample(rng, rows.len(), self.batch_size).into_vec(),
+        };
+
+        let mut classes: BTreeMap<u64, Vec<usize>> = BTreeMap::new();
+        for (n, row) in rows.iter().enumerate() {
+            let label = label(row);
+            classes.entry(label.to_bits()).or_default().push(n);
+        }
+        // Largest remainder repartition of the batch between the labels
+        let quotas: Vec<f64> = classes.values()
+            .map(|c| (self.batch_size as f64) * (c.len() as f64) / (rows.len() as f64))
+            .collect();
+        let mut counts: Vec<usize> = quotas.iter().map(|q| q.floor() as usize).collect();
+        let mut by_remainder: Vec<usize> = (0..quotas.len()).collect();
+        by_remainder.sort_by(|a, b| quotas[*b].fract().partial_cmp(&quotas[*a].fract()).unwrap());
+        let missing = self.batch_size - counts.iter().sum::<usize>();
+        for ind in by_remainder.into_iter().take(missing) {
+            counts[ind] += 1;
+        }
+
+        let mut res = vec![];
+        for (class, count) in classes.values().zip(counts) {
+            res.extend(class.choose_multiple(rng, count).cloned());
+        }
+        res.shuffle(rng);
+        res
+    }
+}
+
+impl<D: Clone> DatasetHandler<D> for MiniBatchDataset<D> {
+    fn prepare(&mut self) {
+        load_rows(&mut self.inner, &mut self.rows);
+        self.order = self.draw_batch(&mut rand::thread_rng());
+        self.pos = 0;
+    }
+
+    fn prepare_full(&mut self) {
+        load_rows(&mut self.inner, &mut self.rows);
+        self.order = (0..self.rows.as_ref().unwrap().len()).collect();
+        self.pos = 0;
+    }
+
+    fn get_next_data(&mut self) -> Option<D> {
+        let ind = *self.order.get(self.pos)?;
+        self.pos += 1;
+        self.rows.as_ref()?.get(ind).cloned()
+    }
+
+    fn reevaluate_elites(&self) -> bool {
+        self.reevaluate_elites
+    }
+
+    fn is_deterministic(&self) -> bool {
+        false
+    }
+}
+
+#[test]
+fn test_minibatch_stratified() {
+    // Labels 0 and 1 in proportions 3/4, 1/4
+    let mut data = vec![];
+    for i in 0..40 {
+        data.push(vec![i as f64, if i % 4 == 0 { 1.0 } else { 0.0 }]);
+    }
+    let inner = Box::new(VecDataset::new(data));
+    let mut batch = MiniBatchDataset::new(inner, 8, Some(1), true).unwrap();
+    batch.prepare();
+    let mut items = vec![];
+    while let Some(d) = batch.get_next_data() {
+        items.push(d);
+    }
+    assert_eq!(items.len(), 8);
+    assert_eq!(items.iter().filter(|d| d[1] == 1.0).count(), 2);
+    batch.prepare_full();
+    let mut nfull = 0;
+    while batch.get_next_data().is_some() {
+        nfull += 1;
+    }
+    assert_eq!(nfull, 40);
+    assert!(MiniBatchDataset::new(Box::new(EmptyDataset::new(1)), 0, None, false).is_err());
+}
+
+#[test]
+fn test_structured_items() {
+    #[derive(Clone)]
+    struct Sample {
+        features: Vec<f64>,
+        label: bool,
+    }
+    let samples = (0..20).map(|i| Sample { features: vec![i as f64], label: i < 5 }).collect();
+    let label: LabelFn<Sample> = Box::new(|s: &Sample| if s.label { 1.0 } else { 0.0 });
+    let mut batch = MiniBatchDataset::with_label(Box::new(VecDataset::new(samples)), 4,
+        Some(label), false).unwrap();
+    batch.prepare();
+    let mut items = vec![];
+    while let Some(s) = batch.get_next_data() {
+        items.push(s);
+    }
+    assert_eq!(items.len(), 4);
+    assert_eq!(items.iter().filter(|s| s.label).count(), 1);
+    assert!(items.iter().all(|s| s.label == (s.features[0] < 5.0)));
+}
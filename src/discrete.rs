@@ -0,0 +1,175 @@
+use crate::cell::Genome;
+use crate::errors::Errcode;
+
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub type BitString = Vec<bool>;
+pub type IntGenome = Vec<i64>;
+
+/*  Kind of value encoded by a gene, genes staying in [0, 1]:
+ *      Real: the gene itself
+ *      Bit: 0.0 or 1.0, true on the upper half
+ *      Integer: min + round(gene * (max - min)), bounds included */
+#[derive(Copy, Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub enum GeneKind {
+    Real,
+    Bit,
+    Integer(i64, i64),
+}
+
+impl GeneKind {
+    pub fn decode(&self, gene: f64) -> f64 {
+        match self {
+            GeneKind::Real => gene,
+            GeneKind::Bit => if gene >= 0.5 { 1.0 } else { 0.0 },
+            GeneKind::Integer(min, max) => {
+                (*min as f64 + gene.clamp(0.0, 1.0) * ((max - min) as f64)).round()
+            }
+        }
+    }
+
+    pub fn encode(&self, value: f64) -> f64 {
+        match self {
+            GeneKind::Real => value,
+            GeneKind::Bit => if value >= 0.5 { 1.0 } else { 0.0 },
+            GeneKind::Integer(min, max) if max > min => {
+                ((value.round() - *min as f64) / ((max - min) as f64)).clamp(0.0, 1.0)
+            }
+            GeneKind::Integer(_, _) => 0.0,
+        }
+    }
+
+    // Gene snapped on the values the kind can encode
+    pub fn snap(&self, gene: f64) -> f64 {
+        self.encode(self.decode(gene))
+    }
+
+    pub fn random(&self, rng: &mut ThreadRng) -> f64 {
+        match self {
+            GeneKind::Real => rng.gen(),
+            GeneKind::Bit => if rng.gen() { 1.0 } else { 0.0 },
+            GeneKind::Integer(min, max) => self.encode(rng.gen_range(*min..=*max) as f64),
+        }
+    }
+
+    /*  Bit-flip for bits, creep mutation for integers (a step of at most creep_step, reversed
+     *  if it goes out of the bounds), reroll for real genes */
+    pub fn mutate(&self, gene: f64, creep_step: i64, rng: &mut ThreadRng) -> f64 {
+        match self {
+            GeneKind::Real => rng.gen(),
+            GeneKind::Bit => 1.0 - self.snap(gene),
+            GeneKind::Integer(min, max) => {
+                let step = rng.gen_range(1..=creep_step.max(1)) * if rng.gen() { 1 } else { -1 };
+                let current = self.decode(gene) as i64;
+                let value = if (*min..=*max).contains(&(current + step)) {
+                    current + step
+                } else {
+                    (current - step).clamp(*min, *max)
+                };
+                self.encode(value as f64)
+            }
+        }
+    }
+}
+
+// Genes beyond the given kinds are real ones
+pub fn gene_kind(kinds: &[GeneKind], gene: usize) -> GeneKind {
+    kinds.get(gene).copied().unwrap_or(GeneKind::Real)
+}
+
+pub fn snap_genome(genome: &mut Genome, kinds: &[GeneKind]) {
+    for (n, g) in genome.iter_mut().enumerate() {
+        *g = gene_kind(kinds, n).snap(*g);
+    }
+}
+
+pub fn bits_to_genome(bits: &BitString) -> Genome {
+    bits.iter().map(|b| if *b { 1.0 } else { 0.0 }).collect()
+}
+
+pub fn bits_from_genome(genome: &Genome) -> BitString {
+    genome.iter().map(|g| *g >= 0.5).collect()
+}
+
+pub fn ints_to_genome(values: &IntGenome, bounds: &[(i64, i64)]) -> Result<Genome, Errcode> {
+    if values.len() != bounds.len() {
+        return Err(Errcode::SizeError("integer genome", bounds.len(), values.len()));
+    }
+    Ok(values.iter().zip(bounds.iter())
+        .map(|(v, (min, max))| GeneKind::Integer(*min, *max).encode(*v as f64))
+        .collect())
+}
+
+pub fn ints_from_genome(genome: &Genome, bounds: &[(i64, i64)]) -> Result<IntGenome, Errcode> {
+    if genome.len() != bounds.len() {
+        return Err(Errcode::SizeError("integer genome", bounds.len(), genome.len()));
+    }
+    Ok(genome.iter().zip(bounds.iter())
+        .map(|(g, (min, max))| GeneKind::Integer(*min, *max).decode(*g) as i64)
+        .collect())
+}
+
+#[derive(Copy, Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub enum DiscreteCrossover {
+    Uniform,        // Each gene from one of the parents
+    KPoint(usize),  // Parents alternating between k random cut points
+}
+
+impl DiscreteCrossover {
+    pub fn apply<G: Clone>(&self, p1: &[G], p2: &[G], rng: &mut ThreadRng) -> Vec<G> {
+        match self {
+            DiscreteCrossover::Uniform => uniform_crossover(p1, p2, rng),
+            DiscreteCrossover::KPoint(k) => k_point_crossover(p1, p2, *k, rng),
+        }
+    }
+}
+
+pub fn uniform_crossover<G: Clone>(p1: &[G], p2: &[G], rng: &mut ThreadRng) -> Vec<G> {
+    p1.iter().zip(p2.iter()).map(|(a, b)| if rng.gen() { a.clone() } else { b.clone() }).collect()
+}
+
+pub fn k_point_crossover<G: Clone>(p1: &[G], p2: &[G], k: usize, rng: &mut ThreadRng) -> Vec<G> {
+    let n = p1.len().min(p2.len());
+    if n < 2 {
+        return p1[..n].to_vec();
+    }
+    let mut cuts = rand::seq::index::sample(rng, n - 1, k.min(n - 1)).into_vec();
+    cuts.iter_mut().for_each(|c| *c += 1);
+    cuts.sort_unstable();
+    let mut child = Vec::with_capacity(n);
+    let mut from_p1 = true;
+    for i in 0..n {
+        if cuts.binary_search(&i).is_ok() {
+            from_p1 = !from_p1;
+        }
+        child.push(if from_p1 { p1[i].clone() } else { p2[i].clone() });
+    }
+    child
+}
+
+#[test]
+fn test_discrete_genomes() {
+    let mut rng = rand::thread_rng();
+    let bits = vec![true, false, true];
+    assert_eq!(bits_from_genome(&bits_to_genome(&bits)), bits);
+    let bounds = [(-2, 2), (0, 10)];
+    let genome = ints_to_genome(&vec![1, 7], &bounds).unwrap();
+    assert_eq!(genome, vec![0.75, 0.7]);
+    assert_eq!(ints_from_genome(&genome, &bounds).unwrap(), vec![1, 7]);
+    assert!(matches!(ints_from_genome(&vec![0.5], &bounds), Err(Errcode::SizeError(_, 2, 1))));
+
+    let int = GeneKind::Integer(0, 3);
+    assert_eq!(int.snap(0.4), 1.0 / 3.0);
+    for _ in 0..100 {
+        let mutant = int.decode(int.mutate(int.encode(3.0), 2, &mut rng));
+        assert!((mutant == 1.0) || (mutant == 2.0));
+    }
+    assert_eq!(GeneKind::Bit.mutate(0.8, 1, &mut rng), 0.0);
+
+    let (p1, p2) = (vec![0; 10], vec![1; 10]);
+    let child = k_point_crossover(&p1, &p2, 2, &mut rng);
+    let switches = child.windows(2).filter(|w| w[0] != w[1]).count();
+    assert!((child[0] == 0) && (switches <= 2) && (child.iter().sum::<i32>() > 0));
+    assert!(uniform_crossover(&p1, &p2, &mut rng).iter().all(|g| *g <= 1));
+}
@@ -0,0 +1,208 @@
+use crate::cell::{CellData, Genome};
+use crate::errors::Errcode;
+use crate::utils::JsonData;
+
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+const MAX_DISTANCE_SAMPLE: usize = 500;
+const ENTROPY_NBINS: usize = 10;
+
+/*  Diversity of a population: mean euclidean distance between two genomes (estimated on
+ *  a sample for big populations), and entropy of each gene over [0, 1], normalised to
+ *  be 1 for uniformly spread values and 0 when all the cells share the same value. */
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct DiversityMeasures {
+    pub mean_distance: f64,
+    pub gene_entropy: Vec<f64>,
+}
+
+impl DiversityMeasures {
+    pub fn compute(genomes: &[&Genome]) -> DiversityMeasures {
+        let ngenes = genomes.iter().map(|g| g.len()).max().unwrap_or(0);
+        DiversityMeasures {
+            mean_distance: mean_pairwise_distance(genomes),
+            gene_entropy: (0..ngenes).map(|n| gene_entropy(genomes, n)).collect(),
+        }
+    }
+
+    pub fn to_json(&self) -> Result<JsonData, Errcode> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    pub fn mean_entropy(&self) -> f64 {
+        if self.gene_entropy.is_empty() {
+            0.0
+        } else {
+            self.gene_entropy.iter().sum::<f64>() / (self.gene_entropy.len() as f64)
+        }
+    }
+}
+
+// Genes missing in the shortest genome count as a difference of the whole scope
+pub fn distance(a: &Genome, b: &Genome) -> f64 {
+    let missing = (a.len() as f64 - b.len() as f64).abs();
+    (a.iter().zip(b.iter()).map(|(x, y)| (x - y).powi(2)).sum::<f64>() + missing).sqrt()
+}
+
+fn mean_pairwise_distance(genomes: &[&Genome]) -> f64 {
+    let sample: Vec<&Genome> = if genomes.len() > MAX_DISTANCE_SAMPLE {
+        genomes.choose_multiple(&mut rand::thread_rng(), MAX_DISTANCE_SAMPLE).cloned().collect()
+    } else {
+        genomes.to_vec()
+    };
+    if sample.len() < 2 {
+        return 0.0;
+    }
+    let mut sum = 0.0;
+    for (n, a) in sample.iter().enumerate() {
+        for b in sample[(n + 1)..].iter() {
+            sum += distance(a, b);
+        }
+    }
+    let npairs = (sample.len() * (sample.len() - 1)) / 2;
+    sum / (npairs as f64)
+}
+
+fn gene_entropy(genomes: &[&Genome], gene: usize) -> f64 {
+    let mut bins = [0usize; ENTROPY_NBINS];
+    let mut tot = 0.0;
+    for val in genomes.iter().filter_map(|g| g.get(gene)) {
+        let bin = (val.max(0.0) * (ENTROPY_NBINS as f64)) as usize;
+        bins[bin.min(ENTROPY_NBINS - 1)] += 1;
+        tot += 1.0;
+    }
+    let entropy: f64 = bins.iter()
+        .filter(|b| **b > 0)
+        .map(|b| { let p = (*b as f64) / tot; -p * p.ln() })
+        .sum();
+    entropy / (ENTROPY_NBINS as f64).ln()
+}
+
+/*  Mechanisms preventing the population to collapse on copies of the same genome
+ *      FitnessSharing: cells are selected on their score degraded by the number of cells
+ *          closer than sigma, weighted by (1 - (d / sigma)^alpha). Scores are supposed
+ *          positive, divided when maximizing and multiplied when minimizing.
+ *      Crowding: in each niche of the given radius around a better cell, only
+ *          niche_capacity cells keep their rank, the other ones are selected last.
+ *      DuplicateElimination: genomes of the next generation equal to another one (gene by
+ *          gene, up to quantum) are resampled randomly. */
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub enum DiversityPreservation {
+    #[default]
+    NoPreservation,
+    FitnessSharing { sigma: f64, alpha: f64 },
+    Crowding { radius: f64, niche_capacity: usize },
+    DuplicateElimination { quantum: f64 },
+}
+
+impl DiversityPreservation {
+    pub fn is_default(&self) -> bool {
+        *self == DiversityPreservation::NoPreservation
+    }
+
+    // Reorders cells sorted by score, scores are left untouched
+    pub fn reorder_cells(&self, cells: &mut Vec<CellData>, maximize: bool) {
+        match self {
+            DiversityPreservation::FitnessSharing { sigma, alpha } => {
+                let shared: Vec<f64> = cells.iter().map(|c| {
+                    let niche: f64 = cells.iter()
+                        .map(|o| distance(&c.genome, &o.genome))
+                        .filter(|d| d < sigma)
+                        .map(|d| 1.0 - (d / sigma).powf(*alpha))
+                        .sum();
+                    if maximize { c.score / niche } else { c.score * niche }
+                }).collect();
+                let mut order: Vec<usize> = (0..cells.len()).collect();
+                if maximize {
+                    order.sort_by(|a, b| shared[*b].partial_cmp(&shared[*a]).unwrap());
+                } else {
+                    order.sort_by(|a, b| shared[*a].partial_cmp(&shared[*b]).unwrap());
+                }
+                reorder(cells, &order);
+            }
+            DiversityPreservation::Crowding { radius, niche_capacity } => {
+                let mut leaders: Vec<(usize, usize)> = vec![];     // (cell, nb in niche)
+                let mut first = vec![];
+                let mut last = vec![];
+                for (n, cell) in cells.iter().enumerate() {
+                    match leaders.iter_mut().find(|(l, _)| distance(&cells[*l].genome, &cell.genome) < *radius) {
+                        Some((_, nb)) if *nb >= *niche_capacity => last.push(n),
+                        Some((_, nb)) => {
+                            *nb += 1;
+                            first.push(n);
+                        }
+                        None => {
+                            leaders.push((n, 1));
+                            first.push(n);
+                        }
+                    }
+                }
+                first.extend(last);
+                reorder(cells, &first);
+            }
+            DiversityPreservation::NoPreservation | DiversityPreservation::DuplicateElimination { .. } => {}
+        }
+    }
+
+    // Resamples the duplicated genomes, only on the genes improved by the algo if set
+    pub fn process_genomes(&self, genomes: &mut [Genome], impr_genes: &Option<Vec<usize>>) -> usize {
+        let quantum = match self {
+            DiversityPreservation::DuplicateElimination { quantum } => *quantum,
+            _ => return 0,
+        };
+        let mut rng = rand::thread_rng();
+        let mut seen = HashSet::new();
+        let mut nresampled = 0;
+        for genome in genomes.iter_mut() {
+            let key: Vec<i64> = genome.iter().map(|g| (g / quantum).round() as i64).collect();
+            if seen.insert(key) {
+                continue;
+            }
+            match impr_genes {
+                Some(genes) => {
+                    for gene in genes.iter() {
+                        if let Some(g) = genome.get_mut(*gene) {
+                            *g = rng.gen();
+                        }
+                    }
+                }
+                None => genome.iter_mut().for_each(|g| *g = rng.gen()),
+            }
+            nresampled += 1;
+        }
+        nresampled
+    }
+}
+
+fn reorder(cells: &mut Vec<CellData>, order: &[usize]) {
+    let reordered = order.iter().map(|n| cells[*n].clone()).collect();
+    *cells = reordered;
+}
+
+#[test]
+fn test_diversity_preservation() {
+    let cell = |genome: Genome, score: f64| CellData { genome, score, version: 0 };
+    let genomes = vec![vec![0.1, 0.1], vec![0.1, 0.1], vec![0.9, 0.9]];
+    let measures = DiversityMeasures::compute(&genomes.iter().collect::<Vec<&Genome>>());
+    assert!((measures.mean_distance - (2.0 * 0.8 * 2f64.sqrt() / 3.0)).abs() < 1e-9);
+    assert!(measures.gene_entropy[0] > 0.0 && measures.gene_entropy[0] < 1.0);
+
+    // Minimizing, two copies of the best cell before a different one
+    let mut cells = vec![cell(vec![0.1, 0.1], 1.0), cell(vec![0.1, 0.1], 1.1), cell(vec![0.9, 0.9], 1.5)];
+    let crowding = DiversityPreservation::Crowding { radius: 0.1, niche_capacity: 1 };
+    crowding.reorder_cells(&mut cells, false);
+    assert_eq!(cells.iter().map(|c| c.score).collect::<Vec<f64>>(), vec![1.0, 1.5, 1.1]);
+
+    let mut cells = vec![cell(vec![0.1, 0.1], 1.0), cell(vec![0.1, 0.1], 1.1), cell(vec![0.9, 0.9], 1.5)];
+    let sharing = DiversityPreservation::FitnessSharing { sigma: 0.5, alpha: 1.0 };
+    sharing.reorder_cells(&mut cells, false);
+    assert_eq!(cells[0].score, 1.5);
+
+    let mut genomes = genomes;
+    let elimination = DiversityPreservation::DuplicateElimination { quantum: 1e-6 };
+    assert_eq!(elimination.process_genomes(&mut genomes, &Some(vec![1])), 1);
+    assert_eq!(genomes[1][0], 0.1);
+    assert!(genomes[1][1] != 0.1);
+}
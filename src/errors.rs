@@ -14,7 +14,22 @@ pub enum Errcode {
     SizeError(&'static str, usize, usize), // Expected, Got
     ValidationError(&'static str),
     JsonSerializationError(Error),
+    TomlSerializationError(String),
+    YamlSerializationError(serde_yaml::Error),
     SpecialDataError(JsonData),
+    AlgoIdDoesntExist(String),
+    DuplicateAlgoId(String),
+    UnknownMethodOption(String, String), // Method, option
+    LabMapFormatDoesntExist(String, Vec<&'static str>), // Name, available
+    IoError(std::io::Error),
+    ColumnDoesntExist(String),
+    DatasetRoleDoesntExist(String),
+    AlgoPopulationTooSmall(String, usize, usize), // Algo id, got, minimum
+    InvalidPopulationWeight(String),
+    GenomeKeyDoesntExist(String),
+    GenomeKeyNotNumeric(String),
+    GenomeKeyInvalid(String),
+    GenomeVersionTooRecent(u64, u64), // Genome version, cell version
 }
 
 impl fmt::Display for Errcode {
@@ -28,6 +43,32 @@ impl fmt::Display for Errcode {
             ),
             Errcode::ValidationError(el) => write!(f, "Error while validating element \"{}\"", el),
             Errcode::SpecialDataError(d) => write!(f, "Error with special data handling: {}", d),
+            Errcode::AlgoIdDoesntExist(id) => write!(f, "No algo with id \"{}\" in the map", id),
+            Errcode::DuplicateAlgoId(id) => write!(f, "Algo id \"{}\" used more than once", id),
+            Errcode::IoError(e) => write!(f, "IO error: {}", e),
+            Errcode::ColumnDoesntExist(col) => write!(f, "Dataset column \"{}\" doesn't exist", col),
+            Errcode::DatasetRoleDoesntExist(role) => write!(f,
+                "Dataset role \"{}\" doesn't exist, available: Training, Validation", role),
+            Errcode::InsuffisantPopulation(got, needed) => write!(f,
+                "Population of {} cells, at least {} needed", got, needed),
+            Errcode::AlgoPopulationTooSmall(id, got, min) => write!(f,
+                "Algo \"{}\" gets {} cells, its method needs at least {}", id, got, min),
+            Errcode::InvalidPopulationWeight(id) => write!(f,
+                "Population weight of algo \"{}\" must be strictly positive", id),
+            Errcode::GenomeKeyDoesntExist(key) => write!(f, "Genome key \"{}\" missing", key),
+            Errcode::GenomeKeyNotNumeric(key) => write!(f, "Genome key \"{}\" is not a number", key),
+            Errcode::GenomeKeyInvalid(key) => write!(f,
+                "Genome key \"{}\" is not a valid path, or conflicts with another key", key),
+            Errcode::GenomeVersionTooRecent(got, current) => write!(f,
+                "Genome version {} is more recent than the cell genome version {}", got, current),
+            Errcode::LabMapFormatDoesntExist(name, available) => write!(
+                f,
+                "Lab map format \"{}\" doesn't exist, available: {:?}",
+                name, available
+            ),
+            Errcode::UnknownMethodOption(method, opt) => {
+                write!(f, "Option \"{}\" doesn't exist for method {}", opt, method)
+            }
             _ => write!(f, "{:?}", self),
         }
     }
@@ -39,3 +80,27 @@ impl From<serde_json::Error> for Errcode {
         Errcode::JsonSerializationError(obj)
     }
 }
+
+impl From<std::io::Error> for Errcode {
+    fn from(obj: std::io::Error) -> Errcode {
+        Errcode::IoError(obj)
+    }
+}
+
+impl From<toml::de::Error> for Errcode {
+    fn from(obj: toml::de::Error) -> Errcode {
+        Errcode::TomlSerializationError(obj.to_string())
+    }
+}
+
+impl From<toml::ser::Error> for Errcode {
+    fn from(obj: toml::ser::Error) -> Errcode {
+        Errcode::TomlSerializationError(obj.to_string())
+    }
+}
+
+impl From<serde_yaml::Error> for Errcode {
+    fn from(obj: serde_yaml::Error) -> Errcode {
+        Errcode::YamlSerializationError(obj)
+    }
+}
@@ -1,26 +1,44 @@
 use std::any::type_name;
 
-use crate::algo::{AlgoConfiguration, AlgoID, AlgoResult, Algo};
-use crate::cell::{Cell, CellData, Genome};
-use crate::dataset::DatasetHandler;
+use crate::algo::{evaluate_genomes, AlgoConfiguration, AlgoID, Algo};
+use crate::cache::EvalCacheStats;
+use crate::cell::{Cell, CellData};
+use crate::dataset::{DatasetHandler, DatasetRole, GenalgoData, MiniBatchDataset, ShuffledDataset,
+    VecDataset};
+use crate::diversity::DiversityMeasures;
 use crate::errors::Errcode;
+use crate::halloffame::{HallOfFame, HallOfFameEntry};
+use crate::history::RunHistory;
 use crate::lab::*;
+use crate::labmaps::mapfile::LabMapFile;
+use crate::migration::GenomeAdapter;
+use crate::restart::ArchivedCell;
+use crate::seeding::{seeds_from_json, SeedFill};
+use crate::sensitivity::{SensitivityAnalysis, SensitivityConfig};
 use crate::utils::cells_from_memory;
 use crate::utils::JsonData;
 
-type LabExport = Vec<(
-    LabConfig,
-    Vec<AlgoConfiguration>,
-    Vec<AlgoResult>,
-    Vec<Genome>,
-)>;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LabExport {
+    pub config: LabConfig,
+    pub map: Vec<AlgoConfiguration>,
+    pub output: Option<AlgoID>,
+    pub hall_of_fame: Vec<HallOfFameEntry>,
+    pub restart_archive: Vec<ArchivedCell>,
+    pub history: RunHistory,
+}
 
 /*  Used to manage labs, get datasets, import / export configurations, binds to Python API,
  *  etc...*/
 pub struct Genalgo<T: Cell> {
     pub lab: Lab<T>,
-    datasets: Vec<Box<dyn DatasetHandler>>,
+    datasets: Vec<Box<dyn DatasetHandler<T::Data>>>,
     datasets_id: Vec<String>,
+    validation_datasets: Vec<Box<dyn DatasetHandler<T::Data>>>,
+    validation_datasets_id: Vec<String>,
+    datasets_order: Vec<String>, // Registration order of the datasets of all roles
 }
 
 impl<T: 'static + Cell> Genalgo<T> {
@@ -34,16 +52,73 @@ impl<T: 'static + Cell> Genalgo<T> {
             lab: Lab::new(labconfig),
             datasets: vec![],
             datasets_id: vec![],
+            validation_datasets: vec![],
+            validation_datasets_id: vec![],
+            datasets_order: vec![],
         }
     }
 
     pub fn export_lab(&self) -> Result<JsonData, Errcode> {
-        let _exports: LabExport = vec![];
-        Err(Errcode::NotImplemented("export_lab"))
+        let export = LabExport {
+            config: *self.lab.get_config(),
+            map: self.lab.get_map().clone(),
+            output: self.lab.out_algo,
+            hall_of_fame: self.lab.get_hall_of_fame().get_entries().to_vec(),
+            restart_archive: self.lab.get_restart_archive().to_vec(),
+            history: self.lab.get_history().clone(),
+        };
+        Ok(serde_json::to_string(&export)?)
+    }
+
+    // Restores a lab exported by export_lab, the algos of its map must already be registered
+    pub fn import_lab(&mut self, data: JsonData) -> Result<(), Errcode> {
+        let export: LabExport = serde_json::from_str(&data)?;
+        self.lab.set_config(export.config)?;
+        self.lab.apply_map(export.map.clone())?;
+        self.__import_lab_results(&export)
+    }
+
+    pub fn import_lab_with_algo<A: 'static + Algo<CellType=T>>(&mut self, data: JsonData
+        ) -> Result<(), Errcode> {
+        let export: LabExport = serde_json::from_str(&data)?;
+        self.lab.set_config(export.config)?;
+        self.lab.apply_map_with_algo::<A>(export.map.clone())?;
+        self.__import_lab_results(&export)
+    }
+
+    // The best cell of the hall of fame is the starting point of every algo
+    fn __import_lab_results(&mut self, export: &LabExport) -> Result<(), Errcode> {
+        self.lab.out_algo = export.output;
+        self.lab.import_hall_of_fame(&export.hall_of_fame)?;
+        if let Some(best) = self.lab.get_hall_of_fame().best() {
+            let genome = best.cell.genome.clone();
+            self.lab.import_best_genome(genome, T::genome_version(), None)?;
+        }
+        Ok(())
     }
 
-    pub fn import_lab(&self, _data: JsonData) -> Result<(), Errcode> {
-        Err(Errcode::NotImplemented("import_lab"))
+    // Seeds given as JSON (see seeding::seeds_from_json), or read from a file
+    pub fn import_seeds(&mut self, jsdata: &str, fill: SeedFill, algo_id: Option<AlgoID>
+        ) -> Result<(), Errcode> {
+        self.lab.import_seed_genomes(seeds_from_json(jsdata)?, fill, algo_id)
+    }
+
+    pub fn import_seeds_from_file(&mut self, path: &str, fill: SeedFill, algo_id: Option<AlgoID>
+        ) -> Result<(), Errcode> {
+        self.import_seeds(&std::fs::read_to_string(path)?, fill, algo_id)
+    }
+
+    // Seeds taken from the hall of fame of the lab, filled by an import_lab or a previous run
+    pub fn seed_from_hall_of_fame(&mut self, fill: SeedFill, algo_id: Option<AlgoID>
+        ) -> Result<(), Errcode> {
+        let seeds = self.lab.get_hall_of_fame().get_entries().iter()
+            .map(|e| (e.cell.genome.clone(), Some(e.cell.version)))
+            .collect();
+        self.lab.import_seed_genomes(seeds, fill, algo_id)
+    }
+
+    pub fn register_genome_adapter(&mut self, from_version: u64, adapter: GenomeAdapter) {
+        self.lab.register_genome_adapter(from_version, adapter);
     }
 
     pub fn test_function(&self) {
@@ -53,26 +128,114 @@ impl<T: 'static + Cell> Genalgo<T> {
         );
     }
 
-    pub fn register_dataset(&mut self, id: String, dataset: Box<dyn DatasetHandler>) {
+    pub fn register_dataset(&mut self, id: String, dataset: Box<dyn DatasetHandler<T::Data>>) {
         self.datasets.push(dataset);
+        self.datasets_order.push(id.clone());
         self.datasets_id.push(id);
+        self.lab.clear_cache();
         println!("{}", self.datasets.len());
     }
 
+    // Emit the items of a registered dataset in a new random order each generation
+    pub fn shuffle_dataset(&mut self, id: String) -> Result<(), Errcode>
+    where
+        T::Data: Clone,
+    {
+        let dataset = self.get_dataset_mut(&id)?;
+        let inner = std::mem::replace(dataset, Box::new(VecDataset::new(vec![])));
+        *dataset = Box::new(ShuffledDataset::new(inner));
+        self.lab.clear_cache();
+        Ok(())
+    }
+
+    // Emit only a random mini-batch of a registered dataset each generation
+    pub fn sample_dataset(&mut self, id: String, batch_size: usize, label_column: Option<usize>,
+        reevaluate_elites: bool) -> Result<(), Errcode>
+    where
+        T: Cell<Data = GenalgoData>,
+    {
+        MiniBatchDataset::<T::Data>::validate_batch_size(batch_size)?;
+        let dataset = self.get_dataset_mut(&id)?;
+        let inner = std::mem::replace(dataset, Box::new(VecDataset::new(vec![])));
+        *dataset = Box::new(MiniBatchDataset::new(inner, batch_size, label_column,
+            reevaluate_elites)?);
+        self.lab.clear_cache();
+        Ok(())
+    }
+
+    fn get_dataset_mut(&mut self, id: &str) -> Result<&mut Box<dyn DatasetHandler<T::Data>>, Errcode> {
+        if let Some(ind) = self.datasets_id.iter().position(|i| i == id) {
+            Ok(&mut self.datasets[ind])
+        } else if let Some(ind) = self.validation_datasets_id.iter().position(|i| i == id) {
+            Ok(&mut self.validation_datasets[ind])
+        } else {
+            Err(Errcode::DatasetDoesntExist(id.to_string()))
+        }
+    }
+
     pub fn remove_dataset(&mut self, id: String) -> Result<(), Errcode> {
-        let ds_ind = self
-            .datasets_id
-            .iter()
-            .enumerate()
-            .filter(|(_, i)| *i == &id)
-            .map(|(n, _)| n)
-            .collect::<Vec<usize>>();
-        if let Some(ind) = ds_ind.get(0) {
-            self.datasets_id.remove(*ind);
-            self.datasets.remove(*ind);
-            Ok(())
+        self.take_dataset(&id)?;
+        self.datasets_order.retain(|i| *i != id);
+        self.lab.clear_cache();
+        Ok(())
+    }
+
+    pub fn register_dataset_with_role(&mut self, id: String, dataset: Box<dyn DatasetHandler<T::Data>>,
+        role: DatasetRole) {
+        match role {
+            DatasetRole::Training => self.register_dataset(id, dataset),
+            DatasetRole::Validation => {
+                self.validation_datasets.push(dataset);
+                self.datasets_order.push(id.clone());
+                self.validation_datasets_id.push(id);
+            }
+        }
+    }
+
+    // The dataset keeps its registration order among the datasets of its new role
+    pub fn set_dataset_role(&mut self, id: String, role: DatasetRole) -> Result<(), Errcode> {
+        if self.get_dataset_role(&id)? == role {
+            return Ok(());
+        }
+        let dataset = self.take_dataset(&id)?;
+        let order = &self.datasets_order;
+        let registered = |i: &String| order.iter().position(|o| o == i);
+        let (ids, datasets) = match role {
+            DatasetRole::Training => (&mut self.datasets_id, &mut self.datasets),
+            DatasetRole::Validation => (&mut self.validation_datasets_id, &mut self.validation_datasets),
+        };
+        let ind = ids.iter().take_while(|i| registered(i) < registered(&id)).count();
+        ids.insert(ind, id);
+        datasets.insert(ind, dataset);
+        self.lab.clear_cache();
+        Ok(())
+    }
+
+    pub fn get_dataset_role(&self, id: &str) -> Result<DatasetRole, Errcode> {
+        if self.datasets_id.iter().any(|i| i == id) {
+            Ok(DatasetRole::Training)
+        } else if self.validation_datasets_id.iter().any(|i| i == id) {
+            Ok(DatasetRole::Validation)
         } else {
-            Err(Errcode::DatasetDoesntExist(id))
+            Err(Errcode::DatasetDoesntExist(id.to_string()))
+        }
+    }
+
+    pub fn list_datasets(&self) -> Vec<(String, DatasetRole)> {
+        let training = self.datasets_id.iter().map(|id| (id.clone(), DatasetRole::Training));
+        let validation = self.validation_datasets_id.iter().map(|id| (id.clone(), DatasetRole::Validation));
+        training.chain(validation).collect()
+    }
+
+    fn take_dataset(&mut self, id: &str) -> Result<Box<dyn DatasetHandler<T::Data>>, Errcode> {
+        if let Some(ind) = self.datasets_id.iter().position(|i| i == id) {
+            self.datasets_id.remove(ind);
+            Ok(self.datasets.remove(ind))
+        } else if let Some(ind) = self.validation_datasets_id.iter().position(|i| i == id) {
+            self.validation_datasets_id.remove(ind);
+            Ok(self.validation_datasets.remove(ind))
+        } else {
+            Err(Errcode::DatasetDoesntExist(id.to_string()))
         }
     }
 
@@ -93,8 +256,107 @@ impl<T: 'static + Cell> Genalgo<T> {
         Ok(())
     }
 
+    pub fn apply_map_file(&mut self, mapfile: LabMapFile) -> Result<(), Errcode> {
+        let map = mapfile.to_map()?;
+        let out = mapfile.get_output_index()?;
+        self.lab.apply_map(map)?;
+        self.__apply_map_file_config(&mapfile, out)
+    }
+
+    pub fn apply_map_file_with_algo<A: 'static + Algo<CellType=T>>(&mut self, mapfile: LabMapFile
+        ) -> Result<(), Errcode> {
+        let map = mapfile.to_map()?;
+        let out = mapfile.get_output_index()?;
+        self.lab.apply_map_with_algo::<A>(map)?;
+        self.__apply_map_file_config(&mapfile, out)
+    }
+
+    pub fn apply_toml_map(&mut self, data: String) -> Result<(), Errcode> {
+        self.apply_map_file(LabMapFile::from_toml(&data)?)
+    }
+
+    pub fn apply_yaml_map(&mut self, data: String) -> Result<(), Errcode> {
+        self.apply_map_file(LabMapFile::from_yaml(&data)?)
+    }
+
+    pub fn apply_toml_map_with_algo<A: 'static + Algo<CellType=T>>(&mut self, data: String
+        ) -> Result<(), Errcode> {
+        self.apply_map_file_with_algo::<A>(LabMapFile::from_toml(&data)?)
+    }
+
+    pub fn apply_yaml_map_with_algo<A: 'static + Algo<CellType=T>>(&mut self, data: String
+        ) -> Result<(), Errcode> {
+        self.apply_map_file_with_algo::<A>(LabMapFile::from_yaml(&data)?)
+    }
+
+    // Once the map is applied, so that an invalid map leaves the lab config untouched
+    fn __apply_map_file_config(&mut self, mapfile: &LabMapFile, out: Option<AlgoID>) -> Result<(), Errcode> {
+        if let Some(labconfig) = mapfile.lab {
+            self.lab.set_config(labconfig)?;
+        }
+        if let Some(out) = out {
+            self.set_output_algorithm(out);
+        }
+        Ok(())
+    }
+
+    pub fn export_map_dot(&self) -> String {
+        self.lab.export_map_graph().to_dot()
+    }
+
+    pub fn export_map_mermaid(&self) -> String {
+        self.lab.export_map_graph().to_mermaid()
+    }
+
+    // Uses an algo already registered in the lab to score the genomes
+    pub fn analyse_sensitivity(&mut self, algo_id: AlgoID, config: &SensitivityConfig
+        ) -> Result<SensitivityAnalysis, Errcode> {
+        let datasets = &mut self.datasets;
+        let algo = match self.lab.algos.get_mut(algo_id) {
+            Some(a) => a,
+            None => return Err(Errcode::IdDoesntExist(algo_id)),
+        };
+        SensitivityAnalysis::run(T::get_genome_length(), config, |genomes| {
+            evaluate_genomes(algo.as_mut(), genomes, datasets).iter().map(|c| c.score).collect()
+        })
+    }
+
+    pub fn analyse_sensitivity_with_algo<A: 'static + Algo<CellType=T>>(&mut self,
+        config: &SensitivityConfig) -> Result<SensitivityAnalysis, Errcode> {
+        let mut algo = A::new();
+        let datasets = &mut self.datasets;
+        SensitivityAnalysis::run(T::get_genome_length(), config, |genomes| {
+            evaluate_genomes(&mut algo, genomes, datasets).iter().map(|c| c.score).collect()
+        })
+    }
+
     pub fn start(&mut self, ngeneration: usize) -> Result<CellData, Errcode> {
-        self.lab.start(ngeneration, &mut self.datasets)
+        self.lab.set_datasets_identity(self.datasets_id.clone());
+        self.lab.start(ngeneration, &mut self.datasets, &mut self.validation_datasets)
+    }
+
+    pub fn get_diversity(&self, id: AlgoID) -> Result<&DiversityMeasures, Errcode> {
+        self.lab.get_diversity(id)
+    }
+
+    pub fn get_hall_of_fame(&self) -> &HallOfFame {
+        self.lab.get_hall_of_fame()
+    }
+
+    pub fn get_restart_counts(&self) -> Vec<usize> {
+        self.lab.get_restart_counts()
+    }
+
+    pub fn get_restart_archive(&self) -> &[ArchivedCell] {
+        self.lab.get_restart_archive()
+    }
+
+    pub fn get_cache_stats(&self) -> Option<EvalCacheStats> {
+        self.lab.get_cache_stats()
+    }
+
+    pub fn get_run_history(&self) -> &RunHistory {
+        self.lab.get_history()
     }
 
     pub fn send_special_data(
@@ -122,3 +384,53 @@ impl<T: 'static + Cell> Genalgo<T> {
     }
 
 }
+
+#[test]
+fn test_export_import_lab() {
+    use crate::cell::Genome;
+    use crate::dataset::EmptyDataset;
+
+    // Written before the genome versions, its cells claim a version the cell type doesn't have
+    crate::cell::test_cell!(OldCell, GenalgoData, 2, data_version: 1);
+    struct OldAlgo;
+    impl Algo for OldAlgo {
+        type CellType = OldCell;
+        fn new() -> Self { OldAlgo }
+        fn genome_from_json(&self, _jsdata: JsonData) -> Result<Genome, Errcode> { Err(Errcode::NotImplemented("genome_from_json")) }
+        fn genome_to_json(&self, _genome: Genome) -> Result<JsonData, Errcode> { Err(Errcode::NotImplemented("genome_to_json")) }
+        fn initialize_cells(&mut self, _pop: &mut Vec<OldCell>) {}
+        fn create_cell_from_genome(&self, genome: &Genome) -> OldCell {
+            OldCell { data: CellData { genome: genome.clone(), score: 0.0, version: 1 } }
+        }
+        fn recv_special_data(&mut self, _data: &serde_json::Value) -> Result<(), JsonData> { Ok(()) }
+        fn send_special_data(&self, _params: &serde_json::Value) -> Result<JsonData, JsonData> { Ok("".to_string()) }
+        fn process_data(&mut self, pop: &mut Vec<OldCell>, _data: &GenalgoData) {
+            for cell in pop.iter_mut() {
+                cell.data.score = 10.0 + cell.data.genome.iter().sum::<f64>();
+            }
+        }
+        fn check_generation_over(&self, _genalgo: &Lab<OldCell>) -> bool { true }
+        fn reset(&mut self) {}
+    }
+
+    let mut genalgo: Genalgo<OldCell> = Genalgo::new(LabConfig::new(100, 0.1, true));
+    genalgo.apply_map_with_algo::<OldAlgo>(serde_json::to_string(&vec![AlgoConfiguration::default()]).unwrap()).unwrap();
+    genalgo.register_dataset("empty".to_string(), Box::new(EmptyDataset::new(1)));
+    genalgo.start(3).unwrap();
+    let entries = genalgo.get_hall_of_fame().get_entries();
+    assert!(!entries.is_empty() && entries.iter().all(|e| e.cell.version == 0));
+
+    let mut imported: Genalgo<OldCell> = Genalgo::new(LabConfig::default());
+    imported.import_lab_with_algo::<OldAlgo>(genalgo.export_lab().unwrap()).unwrap();
+    assert_eq!(imported.get_hall_of_fame().get_entries().len(), entries.len());
+    assert!((imported.get_hall_of_fame().best().unwrap().cell.score - entries[0].cell.score).abs() < 1e-9);
+
+    // Moving a dataset to the validation and back keeps it at its registration position
+    for id in ["a", "b", "c"].iter() {
+        imported.register_dataset(id.to_string(), Box::new(EmptyDataset::new(1)));
+    }
+    imported.set_dataset_role("b".to_string(), DatasetRole::Validation).unwrap();
+    imported.set_dataset_role("b".to_string(), DatasetRole::Training).unwrap();
+    let ids: Vec<String> = imported.list_datasets().into_iter().map(|(id, _)| id).collect();
+    assert_eq!(ids, vec!["a", "b", "c"]);
+}
@@ -1,12 +1,17 @@
 use crate::cell::{Cell, CellData, Genome};
 use crate::errors::Errcode;
+use crate::sampling::InitStrategy;
 use crate::utils::JsonData;
 
+use rand::prelude::*;
 use serde::{Deserialize, Serialize};
 use strum_macros::{EnumIter, EnumString};
 
 mod random_opti;
 mod darwin_method;
+mod permutation_method;
+mod discrete_method;
+mod gp_method;
 
 //TODO  IMPORTANT       Create genome mutation method trait and child creation trait
 //          Use enum_derive to implement the trait directly
@@ -16,10 +21,11 @@ pub trait GenalgoMethod<T: Cell> {
     fn new() -> Self
     where
         Self: Sized;
-    fn json_import(&mut self, jsdata: JsonData) -> Self
+    fn json_import(&mut self, jsdata: JsonData) -> Result<Self, Errcode>
     where
         Self: Sized;
     fn load_config(&mut self, cfg: &GenalgoMethodsConfigurations);
+    fn set_init_strategy(&mut self, init: InitStrategy);
     fn init_population(
         &mut self,
         bestgen: &Genome,
@@ -38,10 +44,18 @@ pub trait GenalgoMethod<T: Cell> {
     fn validate_config(&self) -> Result<(), Errcode>;
 }
 
+// Index of the winner of a tournament, cells being sorted best first
+pub fn tournament_selection(ncells: usize, size: usize, rng: &mut ThreadRng) -> usize {
+    (0..size.max(1)).map(|_| rng.gen_range(0..ncells)).min().unwrap()
+}
+
 #[derive(Copy, Clone, Serialize, Debug, Deserialize, EnumIter, EnumString, strum_macros::ToString)]
 pub enum GenalgoMethodsAvailable {
     RandomOpti,
     Darwin,
+    Permutation,
+    Discrete,
+    GeneticProgramming,
 }
 
 impl GenalgoMethodsAvailable {
@@ -50,6 +64,9 @@ impl GenalgoMethodsAvailable {
         match name.as_str() {
             "Darwin" => Some(GenalgoMethodsAvailable::Darwin),
             "RandomOpti" => Some(GenalgoMethodsAvailable::RandomOpti),
+            "Permutation" => Some(GenalgoMethodsAvailable::Permutation),
+            "Discrete" => Some(GenalgoMethodsAvailable::Discrete),
+            "GeneticProgramming" => Some(GenalgoMethodsAvailable::GeneticProgramming),
             _ => Option::None,
         }
     }
@@ -58,18 +75,47 @@ impl GenalgoMethodsAvailable {
         match self {
             GenalgoMethodsAvailable::Darwin => Box::new(darwin_method::DarwinMethod::new()),
             GenalgoMethodsAvailable::RandomOpti => Box::new(random_opti::RandomOpti::new()),
+            GenalgoMethodsAvailable::Permutation => Box::new(permutation_method::PermutationMethod::new()),
+            GenalgoMethodsAvailable::Discrete => Box::new(discrete_method::DiscreteMethod::new()),
+            GenalgoMethodsAvailable::GeneticProgramming => Box::new(gp_method::GeneticProgramming::new()),
         }
     }
 
     pub fn default() -> GenalgoMethodsAvailable {
         GenalgoMethodsAvailable::Darwin
     }
+
+    pub fn min_elites(&self) -> usize {
+        match self {
+            GenalgoMethodsAvailable::Darwin => 1,
+            GenalgoMethodsAvailable::RandomOpti => 0,
+            GenalgoMethodsAvailable::Permutation => 1,
+            GenalgoMethodsAvailable::Discrete => 1,
+            GenalgoMethodsAvailable::GeneticProgramming => 1,
+        }
+    }
+
+    // Smallest population the method can handle, given the elites it uses (its own and received)
+    pub fn min_population(&self, nelites: usize) -> usize {
+        match self {
+            // Best cell and average best cell are kept, plus at least one non-elite parent
+            GenalgoMethodsAvailable::Darwin => nelites.max(1) + 2,
+            GenalgoMethodsAvailable::RandomOpti => 1,
+            // Elites are kept, plus at least one child
+            GenalgoMethodsAvailable::Permutation
+            | GenalgoMethodsAvailable::Discrete
+            | GenalgoMethodsAvailable::GeneticProgramming => nelites.max(1) + 1,
+        }
+    }
 }
 
 #[derive(Copy, Clone, Serialize, Deserialize, Debug)]
 pub enum GenalgoMethodsConfigurations {
     NoConfig,
     DarwinConfig(darwin_method::DarwinMethodConfiguration),
+    PermutationConfig(permutation_method::PermutationMethodConfiguration),
+    DiscreteConfig(discrete_method::DiscreteMethodConfiguration),
+    GeneticProgrammingConfig(gp_method::GeneticProgrammingConfiguration),
 }
 
 impl GenalgoMethodsConfigurations {
@@ -79,10 +125,52 @@ impl GenalgoMethodsConfigurations {
                 darwin_method::DarwinMethodConfiguration::default(),
             ),
             GenalgoMethodsAvailable::RandomOpti => GenalgoMethodsConfigurations::NoConfig,
+            GenalgoMethodsAvailable::Permutation => GenalgoMethodsConfigurations::PermutationConfig(
+                permutation_method::PermutationMethodConfiguration::default(),
+            ),
+            GenalgoMethodsAvailable::Discrete => GenalgoMethodsConfigurations::DiscreteConfig(
+                discrete_method::DiscreteMethodConfiguration::default(),
+            ),
+            GenalgoMethodsAvailable::GeneticProgramming => GenalgoMethodsConfigurations::GeneticProgrammingConfig(
+                gp_method::GeneticProgrammingConfiguration::default(),
+            ),
         }
     }
 
     pub fn from_str(data: JsonData) -> Result<GenalgoMethodsConfigurations, serde_json::Error> {
         serde_json::from_str(&data)
     }
+
+    // Build the configuration of a method from a set of options overriding its defaults
+    pub fn from_options(
+        method: GenalgoMethodsAvailable,
+        options: &serde_json::Map<String, serde_json::Value>,
+    ) -> Result<GenalgoMethodsConfigurations, Errcode> {
+        let mut cfg = serde_json::to_value(GenalgoMethodsConfigurations::default(method))?;
+        if let Some(tagged) = cfg.as_object_mut() {
+            for (_, fields) in tagged.iter_mut() {
+                let fields = fields.as_object_mut().unwrap();
+                for (key, val) in options.iter() {
+                    if !fields.contains_key(key) {
+                        return Err(Errcode::UnknownMethodOption(method.to_string(), key.clone()));
+                    }
+                    fields.insert(key.clone(), val.clone());
+                }
+            }
+        } else if let Some((key, _)) = options.iter().next() {
+            return Err(Errcode::UnknownMethodOption(method.to_string(), key.clone()));
+        }
+        Ok(serde_json::from_value(cfg)?)
+    }
+
+    // Options of the configuration, without the enum tag
+    pub fn get_options(&self) -> Result<serde_json::Map<String, serde_json::Value>, Errcode> {
+        match serde_json::to_value(self)? {
+            serde_json::Value::Object(tagged) => match tagged.into_iter().next() {
+                Some((_, serde_json::Value::Object(fields))) => Ok(fields),
+                _ => Err(Errcode::CodeError("method configuration format")),
+            },
+            _ => Ok(serde_json::Map::new()),
+        }
+    }
 }
@@ -1,9 +1,10 @@
 use rand::prelude::*;
 use rand_distr::Normal;
 
-use crate::cell::{Cell, CellData, Genome};
+use crate::cell::{genome_length_is_valid, Cell, CellData, Genome};
 use crate::errors::Errcode;
 use crate::genalgomethods::*;
+use crate::sampling::InitStrategy;
 use crate::utils::{MeanComputeVec, StddevComputeVec};
 
 use serde::{Deserialize, Serialize};
@@ -13,6 +14,13 @@ use std::marker::PhantomData;
 pub struct DarwinMethodConfiguration {
     gene_reroll_proba: f64,
     exploration_scope_epoch_max: u32,
+    // Chance to insert or delete a gene, only for cells with variable-length genomes
+    #[serde(default = "default_length_mutation_proba")]
+    length_mutation_proba: f64,
+}
+
+fn default_length_mutation_proba() -> f64 {
+    0.1
 }
 
 impl DarwinMethodConfiguration {
@@ -20,6 +28,7 @@ impl DarwinMethodConfiguration {
         DarwinMethodConfiguration {
             gene_reroll_proba: 0.5,
             exploration_scope_epoch_max: 3,
+            length_mutation_proba: default_length_mutation_proba(),
         }
     }
 }
@@ -61,6 +70,7 @@ pub struct DarwinMethod<T: Cell> {
     config: DarwinMethodConfiguration,
     bestcell_avg: MeanComputeVec,
     last_best_cell: Genome,
+    init: InitStrategy,
     _phantom: PhantomData<T>,
 }
 
@@ -74,15 +84,24 @@ impl<T: Cell> GenalgoMethod<T> for DarwinMethod<T> {
             bestcell_avg: MeanComputeVec::new(T::get_genome_length()),
             epoch_last_new_best: 0,
             config: DarwinMethodConfiguration::default(),
+            init: InitStrategy::default(),
             _phantom: PhantomData,
         }
     }
 
-    fn json_import(&mut self, _jsdata: JsonData) -> Self
+    // Same method with the configuration given as JSON
+    fn json_import(&mut self, jsdata: JsonData) -> Result<Self, Errcode>
     where
         Self: Sized,
     {
-        todo!();
+        Ok(DarwinMethod {
+            last_best_cell: vec![],
+            bestcell_avg: MeanComputeVec::new(T::get_genome_length()),
+            epoch_last_new_best: 0,
+            config: serde_json::from_str(&jsdata)?,
+            init: self.init,
+            _phantom: PhantomData,
+        })
     }
 
     fn reset(&mut self) {
@@ -97,6 +116,10 @@ impl<T: Cell> GenalgoMethod<T> for DarwinMethod<T> {
         .clone();
     }
 
+    fn set_init_strategy(&mut self, init: InitStrategy) {
+        self.init = init;
+    }
+
     fn init_population(
         &mut self,
         bestgen: &Genome,
@@ -106,9 +129,9 @@ impl<T: Cell> GenalgoMethod<T> for DarwinMethod<T> {
     ) -> Result<(), Errcode> {
         if bestgen.len() == 0 {
             return Ok(self.__init_generate_random_population(nb_cells, res));
-        } else if bestgen.len() < T::get_genome_length() {
+        } else if !genome_length_is_valid::<T>(bestgen.len()) {
             return Err(Errcode::CodeError(
-                "best genome length < expected algo length",
+                "best genome length not valid for the cell",
             ));
         } else {
             return Ok(self.__init_generate_population_from_bestgen(
@@ -126,10 +149,16 @@ impl<T: Cell> GenalgoMethod<T> for DarwinMethod<T> {
         cells: &Vec<CellData>,
         genomes: &mut Vec<Genome>,
     ) -> Result<(), Errcode> {
+        if elites.is_empty() || cells.len() < elites.len() + 2 {
+            return Err(Errcode::InsuffisantPopulation(cells.len(), elites.len().max(1) + 2));
+        }
         let mut rng = rand::thread_rng();
 
         if (!self.last_best_cell.is_empty()) & (cells[0].genome != self.last_best_cell) {
             self.epoch_last_new_best = 0;
+            if cells[0].genome.len() != self.bestcell_avg.result.len() {
+                self.bestcell_avg = MeanComputeVec::new(cells[0].genome.len());
+            }
             self.bestcell_avg.add_el(&cells[0].genome, 1.0);
             self.last_best_cell = cells[0].genome.clone();
         } else {
@@ -150,8 +179,12 @@ impl<T: Cell> GenalgoMethod<T> for DarwinMethod<T> {
             min_explo + (max_ratio * rng.gen::<f64>() * (max_explo - min_explo))
         };
 
+        // Only the elites with the length of the best one are averaged
+        let same_length: Vec<&&CellData> = elites.iter()
+            .filter(|e| e.genome.len() == elites[0].genome.len())
+            .collect();
         let mut mean_elite = MeanComputeVec::new(elites[0].genome.len());
-        for elite in elites.iter() {
+        for elite in same_length.iter() {
             mean_elite.add_el(
                 &elite.genome,
                 __transform_score(cells[0].score, elite.score),
@@ -159,7 +192,7 @@ impl<T: Cell> GenalgoMethod<T> for DarwinMethod<T> {
         }
 
         let mut std_elite = StddevComputeVec::new(mean_elite.result.clone());
-        for elite in elites.iter() {
+        for elite in same_length.iter() {
             std_elite.add_el(&elite.genome);
         }
 
@@ -202,7 +235,7 @@ impl<T: Cell> GenalgoMethod<T> for DarwinMethod<T> {
             genomes,
             &mut rng,
         );
-        self.__generate_random_cells(parts_size[5], genomes, &mut rng);
+        self.__generate_random_cells(parts_size[5], genomes);
         Ok(())
     }
 
@@ -219,6 +252,12 @@ impl<T: Cell> GenalgoMethod<T> for DarwinMethod<T> {
             ));
         }
 
+        if !(0.0..=1.0).contains(&self.config.length_mutation_proba) {
+            return Err(Errcode::ValidationError(
+                "Darwin method: length_mutation_proba not in range (0, 1)",
+            ));
+        }
+
         Ok(())
     }
 }
@@ -278,21 +317,18 @@ impl<T: Cell> DarwinMethod<T> {
         genomes.push(bestgen.clone());
 
         let mut rng = rand::thread_rng();
-        for _ in 1..nb_elites {
+        for _ in 1..nb_elites.min(nb_cells) {
             let mut gen = bestgen.clone();
             self.mutate_genome(&mut gen, 0.75, &mut rng);
             genomes.push(gen);
         }
 
-        for _ in 0..(nb_cells - (genomes.len() as u32)) {
-            genomes.push(self.random_genome());
-        }
+        let nrandom = nb_cells.saturating_sub(genomes.len() as u32) as usize;
+        genomes.extend(self.init.sample_genomes::<T>(nrandom));
     }
 
     fn __init_generate_random_population(&mut self, nb_cells: u32, genomes: &mut Vec<Genome>) {
-        for _ in 0..nb_cells {
-            genomes.push(self.random_genome());
-        }
+        genomes.extend(self.init.sample_genomes::<T>(nb_cells as usize));
     }
 
     /*          GENOME MANIPULATION         */
@@ -306,6 +342,7 @@ impl<T: Cell> DarwinMethod<T> {
                 *g = (*g * (1.0 + ((nb - 0.5) * (1.0 + rate)))) % 1.0;
             }
         }
+        self.mutate_length(genome, rate, rng);
     }
 
     fn mutate_genome_direct(&self, genome: &mut Genome, rate: f64, rng: &mut ThreadRng) {
@@ -317,19 +354,30 @@ impl<T: Cell> DarwinMethod<T> {
                 *g = (*g * (1.0 + ((nb - 0.5) * (1.0 + rate)))) % 1.0;
             }
         }
+        self.mutate_length(genome, rate, rng);
     }
 
-    fn random_genome(&self) -> Genome {
-        let mut rng = rand::thread_rng();
-        self.__random_genome(&mut rng, T::get_genome_length())
-    }
-
-    fn __random_genome(&self, rng: &mut ThreadRng, len: usize) -> Genome {
-        let mut res: Genome = vec![];
-        for _ in 0..len {
-            res.push(rng.gen());
+    // Inserts a random gene or deletes one, staying in the length bounds of the cell
+    fn mutate_length(&self, genome: &mut Genome, rate: f64, rng: &mut ThreadRng) {
+        let (min, max) = match T::genome_length_bounds() {
+            Some(bounds) => bounds,
+            None => return,
+        };
+        if rng.gen::<f64>() >= rate * self.config.length_mutation_proba {
+            return;
+        }
+        let grow = if genome.len() <= min {
+            true
+        } else if genome.len() >= max {
+            false
+        } else {
+            rng.gen()
+        };
+        if grow && (genome.len() < max) {
+            genome.insert(rng.gen_range(0..=genome.len()), rng.gen());
+        } else if !grow && (genome.len() > min) {
+            genome.remove(rng.gen_range(0..genome.len()));
         }
-        res
     }
 
     fn __choose_parents(
@@ -351,13 +399,13 @@ impl<T: Cell> DarwinMethod<T> {
         _method: &BreedingMethod,
         rng: &mut ThreadRng,
     ) -> Genome {
-        assert_eq!(p1.genome.len(), p2.genome.len());
         //TODO  Use BreedingMethod enum, taking care of getting good weight if maximization or
         //minimization
         let mut genome = Genome::new();
         let sumscores: f64 = p1.score + p2.score;
         let part = (p1.score / sumscores, p2.score / sumscores);
-        for g in 0..p1.genome.len() {
+        let common = p1.genome.len().min(p2.genome.len());
+        for g in 0..common {
             let num: f64 = rng.gen();
             if num < part.0 {
                 genome.push(p1.genome[g]);
@@ -365,6 +413,11 @@ impl<T: Cell> DarwinMethod<T> {
                 genome.push(p2.genome[g]);
             }
         }
+
+        // Length taken from one of the parents, the tail comes from the longest one
+        let length = if rng.gen::<f64>() < part.0 { p1.genome.len() } else { p2.genome.len() };
+        let longest = if p1.genome.len() > p2.genome.len() { &p1.genome } else { &p2.genome };
+        genome.extend_from_slice(&longest[common..length]);
         genome
     }
 
@@ -460,11 +513,8 @@ impl<T: Cell> DarwinMethod<T> {
         }
     }
 
-    fn __generate_random_cells(&self, size: u32, genvec: &mut Vec<Genome>, rng: &mut ThreadRng) {
-        let genomelen = T::get_genome_length();
-        for _ in 0..size {
-            genvec.push(self.__random_genome(rng, genomelen));
-        }
+    fn __generate_random_cells(&self, size: u32, genvec: &mut Vec<Genome>) {
+        genvec.extend(self.init.sample_genomes::<T>(size as usize));
     }
 
     fn __generate_childs(
@@ -491,3 +541,40 @@ impl<T: Cell> DarwinMethod<T> {
         }
     }
 }
+
+#[test]
+fn test_variable_length_genomes() {
+    crate::cell::test_cell!(VarCell, (), 4, bounds: (2, 6));
+
+    let mut method: DarwinMethod<VarCell> = DarwinMethod::new();
+    let mut cfg = serde_json::to_value(DarwinMethodConfiguration::default()).unwrap();
+    cfg["gene_reroll_proba"] = 0.5.into();
+    let mut method = method.json_import(cfg.to_string()).unwrap();
+    assert!((method.config.gene_reroll_proba - 0.5).abs() < 1e-12);
+    assert!(method.json_import("{\"gene_reroll_proba\": \"high\"}".to_string()).is_err());
+    let mut genomes = vec![];
+    method.init_population(&vec![], 200, 10, &mut genomes).unwrap();
+    assert_eq!(genomes.len(), 200);
+    assert!(genomes.iter().all(|g| genome_length_is_valid::<VarCell>(g.len())));
+    assert!(genomes.iter().any(|g| g.len() != genomes[0].len()));
+    assert!(method.init_population(&vec![0.5; 7], 200, 10, &mut vec![]).is_err());
+
+    for _ in 0..10 {
+        let mut cells: Vec<CellData> = genomes.drain(..)
+            .map(|g| CellData { score: 1.0 + g.iter().sum::<f64>(), genome: g, version: 0 })
+            .collect();
+        cells.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
+        let elites: Vec<&CellData> = cells.iter().take(10).collect();
+        method.process_results(&elites, &cells, &mut genomes).unwrap();
+        assert_eq!(genomes.len(), 200);
+        assert!(genomes.iter().all(|g| genome_length_is_valid::<VarCell>(g.len())));
+    }
+
+    let p1 = CellData { genome: vec![0.1, 0.1], score: 1.0, version: 0 };
+    let p2 = CellData { genome: vec![0.2, 0.2, 0.2, 0.2, 0.2], score: 1.0, version: 0 };
+    let mut rng = rand::thread_rng();
+    for _ in 0..20 {
+        let child = method.__give_birth(&p1, &p2, &BreedingMethod::ScoreBasedChoose, &mut rng);
+        assert!((child.len() == 2) || (child == vec![child[0], child[1], 0.2, 0.2, 0.2]));
+    }
+}
@@ -0,0 +1,172 @@
+use rand::prelude::*;
+
+use crate::cell::{Cell, CellData, Genome};
+use crate::discrete::{gene_kind, snap_genome, DiscreteCrossover, GeneKind};
+use crate::errors::Errcode;
+use crate::genalgomethods::*;
+use crate::sampling::InitStrategy;
+
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+
+#[derive(Copy, Clone, Serialize, Deserialize, Debug)]
+pub struct DiscreteMethodConfiguration {
+    crossover: DiscreteCrossover,
+    crossover_proba: f64,
+    gene_mutation_proba: f64,
+    creep_step: i64,
+    tournament_size: usize,
+}
+
+impl DiscreteMethodConfiguration {
+    pub fn default() -> DiscreteMethodConfiguration {
+        DiscreteMethodConfiguration {
+            crossover: DiscreteCrossover::Uniform,
+            crossover_proba: 0.9,
+            gene_mutation_proba: 0.05,
+            creep_step: 1,
+            tournament_size: 3,
+        }
+    }
+}
+
+/*  Genetic algorithm on bit-string and integer genomes (see discrete.rs, Cell::gene_kinds):
+ *  elites are kept, the rest of the population are children of parents chosen by tournament,
+ *  with uniform or k-point crossover, then bit-flip and creep mutations gene by gene. */
+pub struct DiscreteMethod<T: Cell> {
+    config: DiscreteMethodConfiguration,
+    init: InitStrategy,
+    kinds: Vec<GeneKind>,
+    _phantom: PhantomData<T>,
+}
+
+impl<T: Cell> GenalgoMethod<T> for DiscreteMethod<T> {
+    fn new() -> Self
+    where
+        Self: Sized,
+    {
+        DiscreteMethod {
+            config: DiscreteMethodConfiguration::default(),
+            init: InitStrategy::default(),
+            kinds: T::gene_kinds(),
+            _phantom: PhantomData,
+        }
+    }
+
+    // Same method with the configuration given as JSON
+    fn json_import(&mut self, jsdata: JsonData) -> Result<Self, Errcode>
+    where
+        Self: Sized,
+    {
+        Ok(DiscreteMethod {
+            config: serde_json::from_str(&jsdata)?,
+            init: self.init,
+            kinds: self.kinds.clone(),
+            _phantom: PhantomData,
+        })
+    }
+
+    fn reset(&mut self) {}
+
+    fn load_config(&mut self, cfg: &GenalgoMethodsConfigurations) {
+        self.config = match cfg {
+            GenalgoMethodsConfigurations::DiscreteConfig(c) => *c,
+            _ => unreachable!(),
+        };
+    }
+
+    fn set_init_strategy(&mut self, init: InitStrategy) {
+        self.init = init;
+    }
+
+    fn init_population(
+        &mut self,
+        bestgen: &Genome,
+        nb_cells: u32,
+        nb_elites: u32,
+        res: &mut Vec<Genome>,
+    ) -> Result<(), Errcode> {
+        let mut rng = rand::thread_rng();
+        if !bestgen.is_empty() {
+            let mut best = bestgen.clone();
+            snap_genome(&mut best, &self.kinds);
+            res.push(best.clone());
+            for _ in 1..nb_elites.min(nb_cells) {
+                let mut mutant = best.clone();
+                self.mutate(&mut mutant, &mut rng);
+                res.push(mutant);
+            }
+        }
+        let nrandom = (nb_cells as usize).saturating_sub(res.len());
+        for mut genome in self.init.sample_genomes::<T>(nrandom) {
+            snap_genome(&mut genome, &self.kinds);
+            res.push(genome);
+        }
+        Ok(())
+    }
+
+    fn process_results(
+        &mut self,
+        elites: &Vec<&CellData>,
+        cells: &Vec<CellData>,
+        genomes: &mut Vec<Genome>,
+    ) -> Result<(), Errcode> {
+        if cells.is_empty() {
+            return Err(Errcode::InsuffisantPopulation(0, 1));
+        }
+        let mut rng = rand::thread_rng();
+        for elite in elites.iter().take(cells.len()) {
+            let mut genome = elite.genome.clone();
+            snap_genome(&mut genome, &self.kinds);
+            genomes.push(genome);
+        }
+        for _ in elites.len()..cells.len() {
+            let p1 = &cells[tournament_selection(cells.len(), self.config.tournament_size, &mut rng)];
+            let mut child = if rng.gen::<f64>() < self.config.crossover_proba {
+                let p2 = &cells[tournament_selection(cells.len(), self.config.tournament_size, &mut rng)];
+                self.config.crossover.apply(&p1.genome, &p2.genome, &mut rng)
+            } else {
+                p1.genome.clone()
+            };
+            snap_genome(&mut child, &self.kinds);
+            self.mutate(&mut child, &mut rng);
+            genomes.push(child);
+        }
+        Ok(())
+    }
+
+    fn validate_config(&self) -> Result<(), Errcode> {
+        if !(0.0..=1.0).contains(&self.config.crossover_proba) {
+            return Err(Errcode::ValidationError(
+                "Discrete method: crossover_proba not in range (0, 1)",
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.config.gene_mutation_proba) {
+            return Err(Errcode::ValidationError(
+                "Discrete method: gene_mutation_proba not in range (0, 1)",
+            ));
+        }
+        if self.config.creep_step < 1 {
+            return Err(Errcode::ValidationError("Discrete method: creep_step < 1"));
+        }
+        if self.config.tournament_size == 0 {
+            return Err(Errcode::ValidationError(
+                "Discrete method: tournament_size == 0",
+            ));
+        }
+        if self.kinds.iter().any(|k| matches!(k, GeneKind::Integer(min, max) if min > max)) {
+            return Err(Errcode::ValidationError("Discrete method: integer gene with min > max"));
+        }
+        Ok(())
+    }
+}
+
+impl<T: Cell> DiscreteMethod<T> {
+    fn mutate(&self, genome: &mut Genome, rng: &mut ThreadRng) {
+        for (n, g) in genome.iter_mut().enumerate() {
+            if rng.gen::<f64>() < self.config.gene_mutation_proba {
+                *g = gene_kind(&self.kinds, n).mutate(*g, self.config.creep_step, rng);
+            }
+        }
+    }
+}
@@ -0,0 +1,236 @@
+use rand::prelude::*;
+
+use crate::cell::{genome_length_is_valid, Cell, CellData, Genome};
+use crate::errors::Errcode;
+use crate::genalgomethods::*;
+use crate::gp::{subtree_crossover, PrimitiveSet, Tree};
+use crate::sampling::InitStrategy;
+
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+
+#[derive(Copy, Clone, Serialize, Deserialize, Debug)]
+pub struct GeneticProgrammingConfiguration {
+    crossover_proba: f64,
+    point_mutation_proba: f64,
+    subtree_mutation_proba: f64,
+    tournament_size: usize,
+    init_min_depth: usize,
+    init_max_depth: usize,
+    mutation_max_depth: usize,
+    max_depth: usize,
+}
+
+impl GeneticProgrammingConfiguration {
+    pub fn default() -> GeneticProgrammingConfiguration {
+        GeneticProgrammingConfiguration {
+            crossover_proba: 0.8,
+            point_mutation_proba: 0.1,
+            subtree_mutation_proba: 0.05,
+            tournament_size: 5,
+            init_min_depth: 2,
+            init_max_depth: 5,
+            mutation_max_depth: 3,
+            max_depth: 10,
+        }
+    }
+}
+
+/*  Genetic programming on the trees encoded in the genomes (see gp.rs, Cell::primitive_set):
+ *  ramped half-and-half initial population, elites kept, children of parents chosen by
+ *  tournament made by subtree crossover, point or subtree mutation, or copy.
+ *  Against bloat, a child deeper than max_depth or with a genome longer than the cell allows
+ *  is replaced by its first parent. Trees coming from outside this method (imported best
+ *  genome, elites, parents) beyond these limits are replaced by new random trees. */
+pub struct GeneticProgramming<T: Cell> {
+    config: GeneticProgrammingConfiguration,
+    primitives: Option<PrimitiveSet>,
+    _phantom: PhantomData<T>,
+}
+
+impl<T: Cell> GenalgoMethod<T> for GeneticProgramming<T> {
+    fn new() -> Self
+    where
+        Self: Sized,
+    {
+        GeneticProgramming {
+            config: GeneticProgrammingConfiguration::default(),
+            primitives: T::primitive_set(),
+            _phantom: PhantomData,
+        }
+    }
+
+    // Same method with the configuration given as JSON
+    fn json_import(&mut self, jsdata: JsonData) -> Result<Self, Errcode>
+    where
+        Self: Sized,
+    {
+        Ok(GeneticProgramming {
+            config: serde_json::from_str(&jsdata)?,
+            primitives: self.primitives.clone(),
+            _phantom: PhantomData,
+        })
+    }
+
+    fn reset(&mut self) {}
+
+    fn load_config(&mut self, cfg: &GenalgoMethodsConfigurations) {
+        self.config = match cfg {
+            GenalgoMethodsConfigurations::GeneticProgrammingConfig(c) => *c,
+            _ => unreachable!(),
+        };
+    }
+
+    // Initial trees are always ramped half-and-half
+    fn set_init_strategy(&mut self, _init: InitStrategy) {}
+
+    fn init_population(
+        &mut self,
+        bestgen: &Genome,
+        nb_cells: u32,
+        nb_elites: u32,
+        res: &mut Vec<Genome>,
+    ) -> Result<(), Errcode> {
+        let set = match &self.primitives {
+            Some(set) => set,
+            None => return Err(Errcode::NotSet("primitive set of the cell")),
+        };
+        set.validate()?;
+        let mut rng = rand::thread_rng();
+        if !bestgen.is_empty() {
+            let best = set.decode(&self.__keep_in_limits(set, &[&set.decode(bestgen)], &mut rng));
+            res.push(set.encode(&best));
+            for _ in 1..nb_elites.min(nb_cells) {
+                let mut mutant = best.clone();
+                set.subtree_mutation(&mut mutant, self.config.mutation_max_depth, &mut rng);
+                res.push(self.__keep_in_limits(set, &[&mutant, &best], &mut rng));
+            }
+        }
+        let nrandom = (nb_cells as usize).saturating_sub(res.len());
+        let (min_depth, max_depth) = (self.config.init_min_depth, self.config.init_max_depth);
+        for tree in set.ramped_half_and_half(nrandom, min_depth, max_depth, &mut rng) {
+            res.push(self.__keep_in_limits(set, &[&tree], &mut rng));
+        }
+        Ok(())
+    }
+
+    fn process_results(
+        &mut self,
+        elites: &Vec<&CellData>,
+        cells: &Vec<CellData>,
+        genomes: &mut Vec<Genome>,
+    ) -> Result<(), Errcode> {
+        let set = match &self.primitives {
+            Some(set) => set,
+            None => return Err(Errcode::NotSet("primitive set of the cell")),
+        };
+        if cells.is_empty() {
+            return Err(Errcode::InsuffisantPopulation(0, 1));
+        }
+        let mut rng = rand::thread_rng();
+        let trees: Vec<Tree> = cells.iter().map(|c| set.decode(&c.genome)).collect();
+        for elite in elites.iter().take(cells.len()) {
+            genomes.push(self.__keep_in_limits(set, &[&set.decode(&elite.genome)], &mut rng));
+        }
+        let cfg = &self.config;
+        for _ in elites.len()..cells.len() {
+            let p1 = &trees[tournament_selection(cells.len(), cfg.tournament_size, &mut rng)];
+            let mut child = p1.clone();
+            let op: f64 = rng.gen();
+            if op < cfg.crossover_proba {
+                let p2 = &trees[tournament_selection(cells.len(), cfg.tournament_size, &mut rng)];
+                child = subtree_crossover(p1, p2, &mut rng);
+            } else if op < cfg.crossover_proba + cfg.point_mutation_proba {
+                set.point_mutation(&mut child, &mut rng);
+            } else if op < cfg.crossover_proba + cfg.point_mutation_proba + cfg.subtree_mutation_proba {
+                set.subtree_mutation(&mut child, cfg.mutation_max_depth, &mut rng);
+            }
+            genomes.push(self.__keep_in_limits(set, &[&child, p1], &mut rng));
+        }
+        Ok(())
+    }
+
+    fn validate_config(&self) -> Result<(), Errcode> {
+        let cfg = &self.config;
+        let probas = [cfg.crossover_proba, cfg.point_mutation_proba, cfg.subtree_mutation_proba];
+        if probas.iter().any(|p| *p < 0.0) || (probas.iter().sum::<f64>() > 1.0) {
+            return Err(Errcode::ValidationError(
+                "Genetic programming: operators probabilities not in range (0, 1)",
+            ));
+        }
+        if cfg.tournament_size == 0 {
+            return Err(Errcode::ValidationError(
+                "Genetic programming: tournament_size == 0",
+            ));
+        }
+        if (cfg.init_min_depth == 0) || (cfg.init_min_depth > cfg.init_max_depth)
+            || (cfg.init_max_depth > cfg.max_depth) {
+            return Err(Errcode::ValidationError(
+                "Genetic programming: depths, 0 < init_min_depth <= init_max_depth <= max_depth",
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl<T: Cell> GeneticProgramming<T> {
+    // Genome of the first tree within the depth and length limits, else of a new random tree
+    fn __keep_in_limits(&self, set: &PrimitiveSet, trees: &[&Tree], rng: &mut ThreadRng) -> Genome {
+        for tree in trees.iter() {
+            let genome = set.encode(tree);
+            if self.__within_limits(tree, &genome) {
+                return genome;
+            }
+        }
+        let cfg = &self.config;
+        let terminal = Tree { nodes: vec![set.random_terminal(rng)] };
+        let random = set.ramped_half_and_half(1, cfg.init_min_depth, cfg.init_max_depth, rng).remove(0);
+        let genome = set.encode(&random);
+        if self.__within_limits(&random, &genome) {
+            genome
+        } else {
+            set.encode(&terminal)
+        }
+    }
+
+    fn __within_limits(&self, tree: &Tree, genome: &Genome) -> bool {
+        (tree.depth() <= self.config.max_depth) && genome_length_is_valid::<T>(genome.len())
+    }
+}
+
+#[test]
+fn test_gp_bloat_limits() {
+    use crate::gp::GpFunction;
+
+    crate::cell::test_cell!(TreeCell, (), 1, bounds: (1, 20), {
+        fn primitive_set() -> Option<PrimitiveSet> {
+            Some(PrimitiveSet { functions: vec![GpFunction::Add, GpFunction::Sin], nvars: 1, constants: None })
+        }
+    });
+
+    let mut method: GeneticProgramming<TreeCell> = GeneticProgramming::new();
+    let mut cfg = serde_json::to_value(GeneticProgrammingConfiguration::default()).unwrap();
+    cfg["init_max_depth"] = 3.into();
+    cfg["max_depth"] = 4.into();
+    let mut method = method.json_import(cfg.to_string()).unwrap();
+    method.validate_config().unwrap();
+    let set = TreeCell::primitive_set().unwrap();
+    let within_limits = |genomes: &Vec<Genome>| genomes.iter()
+        .all(|g| (g.len() <= 20) && (set.decode(g).depth() <= 4) && (set.encode(&set.decode(g)) == *g));
+
+    // Genomes made elsewhere, decoded as trees of 513 nodes
+    let bloated = vec![0.0; 256];
+    let mut genomes = vec![];
+    method.init_population(&bloated, 50, 5, &mut genomes).unwrap();
+    assert!(genomes.len() == 50 && within_limits(&genomes));
+
+    let cells: Vec<CellData> = (0..50)
+        .map(|n| CellData { genome: if n % 2 == 0 { bloated.clone() } else { genomes[n].clone() }, score: n as f64, version: 0 })
+        .collect();
+    let elites: Vec<&CellData> = cells.iter().take(5).collect();
+    for _ in 0..10 {
+        let mut next = vec![];
+        method.process_results(&elites, &cells, &mut next).unwrap();
+        assert!(next.len() == 50 && within_limits(&next));
+    }
+}
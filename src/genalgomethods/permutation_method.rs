@@ -0,0 +1,155 @@
+use rand::prelude::*;
+
+use crate::cell::{Cell, CellData, Genome};
+use crate::errors::Errcode;
+use crate::genalgomethods::*;
+use crate::permutation::{self, Permutation, PermutationCrossover, PermutationMutation};
+use crate::sampling::InitStrategy;
+
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+
+#[derive(Copy, Clone, Serialize, Deserialize, Debug)]
+pub struct PermutationMethodConfiguration {
+    crossover: PermutationCrossover,
+    mutation: PermutationMutation,
+    crossover_proba: f64,
+    mutation_proba: f64,
+    tournament_size: usize,
+}
+
+impl PermutationMethodConfiguration {
+    pub fn default() -> PermutationMethodConfiguration {
+        PermutationMethodConfiguration {
+            crossover: PermutationCrossover::Order,
+            mutation: PermutationMutation::Inversion,
+            crossover_proba: 0.9,
+            mutation_proba: 0.3,
+            tournament_size: 3,
+        }
+    }
+}
+
+/*  Genetic algorithm on permutation genomes (see permutation.rs): elites are kept, the rest of
+ *  the population are children of parents chosen by tournament, crossed and mutated with the
+ *  permutation operators so every genome stays a valid permutation. */
+pub struct PermutationMethod<T: Cell> {
+    config: PermutationMethodConfiguration,
+    init: InitStrategy,
+    _phantom: PhantomData<T>,
+}
+
+impl<T: Cell> GenalgoMethod<T> for PermutationMethod<T> {
+    fn new() -> Self
+    where
+        Self: Sized,
+    {
+        PermutationMethod {
+            config: PermutationMethodConfiguration::default(),
+            init: InitStrategy::default(),
+            _phantom: PhantomData,
+        }
+    }
+
+    // Same method with the configuration given as JSON
+    fn json_import(&mut self, jsdata: JsonData) -> Result<Self, Errcode>
+    where
+        Self: Sized,
+    {
+        Ok(PermutationMethod {
+            config: serde_json::from_str(&jsdata)?,
+            init: self.init,
+            _phantom: PhantomData,
+        })
+    }
+
+    fn reset(&mut self) {}
+
+    fn load_config(&mut self, cfg: &GenalgoMethodsConfigurations) {
+        self.config = match cfg {
+            GenalgoMethodsConfigurations::PermutationConfig(c) => *c,
+            _ => unreachable!(),
+        };
+    }
+
+    fn set_init_strategy(&mut self, init: InitStrategy) {
+        self.init = init;
+    }
+
+    fn init_population(
+        &mut self,
+        bestgen: &Genome,
+        nb_cells: u32,
+        nb_elites: u32,
+        res: &mut Vec<Genome>,
+    ) -> Result<(), Errcode> {
+        let mut rng = rand::thread_rng();
+        if !bestgen.is_empty() {
+            if bestgen.len() != T::get_genome_length() {
+                return Err(Errcode::SizeError("best genome", T::get_genome_length(), bestgen.len()));
+            }
+            let best = permutation::from_genome(bestgen);
+            res.push(permutation::to_genome(&best));
+            for _ in 1..nb_elites.min(nb_cells) {
+                let mut mutant = best.clone();
+                self.config.mutation.apply(&mut mutant, &mut rng);
+                res.push(permutation::to_genome(&mutant));
+            }
+        }
+        // Random keys of the init strategy, decoded to permutations
+        let nrandom = (nb_cells as usize).saturating_sub(res.len());
+        for genome in self.init.sample(nrandom, T::get_genome_length()) {
+            res.push(permutation::to_genome(&permutation::from_genome(&genome)));
+        }
+        Ok(())
+    }
+
+    fn process_results(
+        &mut self,
+        elites: &Vec<&CellData>,
+        cells: &Vec<CellData>,
+        genomes: &mut Vec<Genome>,
+    ) -> Result<(), Errcode> {
+        if cells.is_empty() {
+            return Err(Errcode::InsuffisantPopulation(0, 1));
+        }
+        let mut rng = rand::thread_rng();
+        let perms: Vec<Permutation> = cells.iter().map(|c| permutation::from_genome(&c.genome)).collect();
+        for elite in elites.iter().take(cells.len()) {
+            genomes.push(permutation::to_genome(&permutation::from_genome(&elite.genome)));
+        }
+        for _ in elites.len()..cells.len() {
+            let p1 = &perms[tournament_selection(cells.len(), self.config.tournament_size, &mut rng)];
+            let mut child = if rng.gen::<f64>() < self.config.crossover_proba {
+                let p2 = &perms[tournament_selection(cells.len(), self.config.tournament_size, &mut rng)];
+                self.config.crossover.apply(p1, p2, &mut rng)
+            } else {
+                p1.clone()
+            };
+            if rng.gen::<f64>() < self.config.mutation_proba {
+                self.config.mutation.apply(&mut child, &mut rng);
+            }
+            genomes.push(permutation::to_genome(&child));
+        }
+        Ok(())
+    }
+
+    fn validate_config(&self) -> Result<(), Errcode> {
+        if !(0.0..=1.0).contains(&self.config.crossover_proba) {
+            return Err(Errcode::ValidationError(
+                "Permutation method: crossover_proba not in range (0, 1)",
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.config.mutation_proba) {
+            return Err(Errcode::ValidationError(
+                "Permutation method: mutation_proba not in range (0, 1)",
+            ));
+        }
+        if self.config.tournament_size == 0 {
+            return Err(Errcode::ValidationError(
+                "Permutation method: tournament_size == 0",
+            ));
+        }
+        Ok(())
+    }
+}
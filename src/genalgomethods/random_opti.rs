@@ -1,36 +1,41 @@
 use crate::genalgomethods::{GenalgoMethod, GenalgoMethodsConfigurations};
-use crate::cell::{Genome, Cell, CellData, random_genome};
+use crate::cell::{Genome, Cell, CellData};
+use crate::sampling::InitStrategy;
 use crate::errors::Errcode;
 use crate::utils::JsonData;
 
 use std::marker::PhantomData;
 
 pub struct RandomOpti<T: Cell>{
+    init: InitStrategy,
     _phantom: PhantomData<T>,
 }
 
 impl<T: Cell> GenalgoMethod<T> for RandomOpti<T> {
     fn new() -> Self where Self: Sized{
-        RandomOpti { _phantom: PhantomData }
+        RandomOpti { init: InitStrategy::default(), _phantom: PhantomData }
     }
 
-    fn json_import(&mut self, _jsdata: JsonData) -> Self where Self: Sized{
-        todo!();
+    // No configuration to import, only the init strategy is kept
+    fn json_import(&mut self, _jsdata: JsonData) -> Result<Self, Errcode> where Self: Sized{
+        Ok(RandomOpti { init: self.init, _phantom: PhantomData })
     }
 
     fn load_config(&mut self, _cfg: &GenalgoMethodsConfigurations){}
 
+    fn set_init_strategy(&mut self, init: InitStrategy){
+        self.init = init;
+    }
+
 
     fn init_population(
         &mut self,
-        bestgen: &Genome,
+        _bestgen: &Genome,
         nb_cells: u32,
         _nb_elites: u32,
         res: &mut Vec<Genome>,
     ) -> Result<(), Errcode>{
-        for _ in 0..nb_cells{
-            res.push(random_genome(bestgen.len()));
-        }
+        res.extend(self.init.sample_genomes::<T>(nb_cells as usize));
         Ok(())
     }
 
@@ -40,10 +45,7 @@ impl<T: Cell> GenalgoMethod<T> for RandomOpti<T> {
         cells: &Vec<CellData>,
         genomes: &mut Vec<Genome>,
     ) -> Result<(), Errcode>{
-        let ngens = cells[0].genome.len();
-        for _ in 0..cells.len(){
-            genomes.push(random_genome(ngens));
-        }
+        genomes.extend(self.init.sample_genomes::<T>(cells.len()));
         Ok(())
     }
 
@@ -0,0 +1,93 @@
+use crate::cell::Genome;
+use crate::discrete::GeneKind;
+use crate::errors::Errcode;
+use crate::utils::JsonData;
+
+pub use petridish_derive::Genome;
+
+/*  Typed parameters of a cell, encoded in a genome with one gene per field.
+ *  Genes are in [0, 1] and scaled to the bounds of their field, integer fields are rounded
+ *  and boolean ones are true on the upper half of their bounds (see KINDS, to give to
+ *  Cell::gene_kinds).
+ *  Implemented with #[derive(Genome)], bounds set with #[genome(min = .., max = ..)], for
+ *  fields of float, integer or bool types only. */
+pub trait GenomeParams: Sized {
+    const KEYS: &'static [&'static str];
+    const BOUNDS: &'static [(f64, f64)];
+    const KINDS: &'static [GeneKind];
+
+    // Values of the fields, in the bounds, in the order of KEYS
+    fn from_values(values: &[f64]) -> Self;
+    fn to_values(&self) -> Vec<f64>;
+
+    fn genome_length() -> usize {
+        Self::KEYS.len()
+    }
+
+    fn from_genome(genome: &Genome) -> Result<Self, Errcode> {
+        if genome.len() != Self::KEYS.len() {
+            return Err(Errcode::SizeError("genome", Self::KEYS.len(), genome.len()));
+        }
+        let values: Vec<f64> = genome.iter().zip(Self::BOUNDS.iter())
+            .map(|(g, (min, max))| min + g.clamp(0.0, 1.0) * (max - min))
+            .collect();
+        Ok(Self::from_values(&values))
+    }
+
+    fn to_genome(&self) -> Genome {
+        self.to_values().iter().zip(Self::BOUNDS.iter())
+            .map(|(v, (min, max))| if max > min { ((v - min) / (max - min)).clamp(0.0, 1.0) } else { 0.0 })
+            .collect()
+    }
+
+    // JSON object of the field values, keyed by field name
+    fn genome_to_json(genome: &Genome) -> Result<JsonData, Errcode> {
+        let values = Self::from_genome(genome)?.to_values();
+        let mut res = serde_json::Map::new();
+        for (key, val) in Self::KEYS.iter().zip(values) {
+            res.insert(key.to_string(), serde_json::json!(val));
+        }
+        Ok(serde_json::to_string(&res)?)
+    }
+
+    fn genome_from_json(jsdata: &str) -> Result<Genome, Errcode> {
+        let data: serde_json::Value = serde_json::from_str(jsdata)?;
+        let mut values = vec![];
+        for key in Self::KEYS.iter() {
+            match data.get(key) {
+                Some(v) => match v.as_f64().or_else(|| v.as_bool().map(|b| if b { 1.0 } else { 0.0 })) {
+                    Some(val) => values.push(val),
+                    None => return Err(Errcode::GenomeKeyNotNumeric(key.to_string())),
+                },
+                None => return Err(Errcode::GenomeKeyDoesntExist(key.to_string())),
+            }
+        }
+        Ok(Self::from_values(&values).to_genome())
+    }
+}
+
+#[test]
+fn test_derive_genome() {
+    #[derive(Genome, Debug, PartialEq)]
+    struct TestParams {
+        #[genome(min = -1.0, max = 3.0)]
+        speed: f64,
+        #[genome(min = 1, max = 5)]
+        layers: usize,
+        enabled: bool,
+    }
+
+    assert_eq!(TestParams::genome_length(), 3);
+    assert_eq!(TestParams::KINDS, &[GeneKind::Real, GeneKind::Integer(1, 5), GeneKind::Bit]);
+    let params = TestParams::from_genome(&vec![0.5, 0.6, 0.2]).unwrap();
+    assert_eq!(params, TestParams { speed: 1.0, layers: 3, enabled: false });
+    assert_eq!(params.to_genome(), vec![0.5, 0.5, 0.0]);
+
+    let json = TestParams::genome_to_json(&vec![0.25, 1.0, 0.9]).unwrap();
+    let genome = TestParams::genome_from_json(&json).unwrap();
+    assert_eq!(TestParams::from_genome(&genome).unwrap(),
+        TestParams { speed: 0.0, layers: 5, enabled: true });
+    assert!(matches!(TestParams::genome_from_json("{\"speed\": 1.0}"),
+        Err(Errcode::GenomeKeyDoesntExist(k)) if k == "layers"));
+    assert!(matches!(TestParams::from_genome(&vec![0.5]), Err(Errcode::SizeError(_, 3, 1))));
+}
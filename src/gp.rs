@@ -0,0 +1,356 @@
+use crate::cell::Genome;
+use crate::errors::Errcode;
+
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+mod regression;
+pub use regression::{GpSpec, SymbolicRegressionAlgo, SymbolicRegressionCell};
+
+// Protected versions of the functions, so any tree evaluates to a number
+#[derive(Copy, Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub enum GpFunction {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Neg,
+    Sin,
+    Cos,
+    Exp,
+    Log,
+}
+
+impl GpFunction {
+    pub fn arity(&self) -> usize {
+        match self {
+            GpFunction::Add | GpFunction::Sub | GpFunction::Mul | GpFunction::Div => 2,
+            _ => 1,
+        }
+    }
+
+    pub fn apply(&self, args: &[f64]) -> f64 {
+        match self {
+            GpFunction::Add => args[0] + args[1],
+            GpFunction::Sub => args[0] - args[1],
+            GpFunction::Mul => args[0] * args[1],
+            GpFunction::Div => if args[1].abs() < 1e-9 { 1.0 } else { args[0] / args[1] },
+            GpFunction::Neg => -args[0],
+            GpFunction::Sin => args[0].sin(),
+            GpFunction::Cos => args[0].cos(),
+            GpFunction::Exp => args[0].min(50.0).exp(),
+            GpFunction::Log => if args[0].abs() < 1e-9 { 0.0 } else { args[0].abs().ln() },
+        }
+    }
+
+    fn symbol(&self) -> &'static str {
+        match self {
+            GpFunction::Add => "+",
+            GpFunction::Sub => "-",
+            GpFunction::Mul => "*",
+            GpFunction::Div => "/",
+            GpFunction::Neg => "neg",
+            GpFunction::Sin => "sin",
+            GpFunction::Cos => "cos",
+            GpFunction::Exp => "exp",
+            GpFunction::Log => "log",
+        }
+    }
+}
+
+#[derive(Copy, Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub enum GpNode {
+    Func(GpFunction),
+    Var(usize),
+    Const(f64),
+}
+
+impl GpNode {
+    pub fn arity(&self) -> usize {
+        match self {
+            GpNode::Func(f) => f.arity(),
+            _ => 0,
+        }
+    }
+}
+
+// Expression tree, its nodes stored in prefix order
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct Tree {
+    pub nodes: Vec<GpNode>,
+}
+
+impl Tree {
+    pub fn size(&self) -> usize {
+        self.nodes.len()
+    }
+
+    // End (excluded) of the subtree starting at the given node
+    pub fn subtree_end(&self, start: usize) -> usize {
+        let mut missing = 1;
+        let mut end = start;
+        while (missing > 0) && (end < self.nodes.len()) {
+            missing = missing - 1 + self.nodes[end].arity();
+            end += 1;
+        }
+        end
+    }
+
+    pub fn depth(&self) -> usize {
+        let mut stack: Vec<usize> = vec![];
+        for node in self.nodes.iter().rev() {
+            let children = (0..node.arity()).map(|_| stack.pop().unwrap_or(0)).max().unwrap_or(0);
+            stack.push(children + 1);
+        }
+        stack.pop().unwrap_or(0)
+    }
+
+    // Variables missing from the inputs are 0
+    pub fn eval(&self, inputs: &[f64]) -> f64 {
+        let mut stack: Vec<f64> = vec![];
+        for node in self.nodes.iter().rev() {
+            let val = match node {
+                GpNode::Func(f) => {
+                    let args: Vec<f64> = (0..f.arity()).map(|_| stack.pop().unwrap_or(0.0)).collect();
+                    f.apply(&args)
+                }
+                GpNode::Var(n) => inputs.get(*n).copied().unwrap_or(0.0),
+                GpNode::Const(c) => *c,
+            };
+            stack.push(val);
+        }
+        stack.pop().unwrap_or(0.0)
+    }
+
+    fn __fmt_node(&self, n: usize, f: &mut fmt::Formatter) -> Result<usize, fmt::Error> {
+        match self.nodes.get(n) {
+            Some(GpNode::Func(func)) if func.arity() == 2 => {
+                write!(f, "(")?;
+                let next = self.__fmt_node(n + 1, f)?;
+                write!(f, " {} ", func.symbol())?;
+                let next = self.__fmt_node(next, f)?;
+                write!(f, ")")?;
+                Ok(next)
+            }
+            Some(GpNode::Func(func)) => {
+                write!(f, "{}(", func.symbol())?;
+                let next = self.__fmt_node(n + 1, f)?;
+                write!(f, ")")?;
+                Ok(next)
+            }
+            Some(GpNode::Var(v)) => write!(f, "x{}", v).map(|_| n + 1),
+            Some(GpNode::Const(c)) => write!(f, "{:.4}", c).map(|_| n + 1),
+            None => Ok(n),
+        }
+    }
+}
+
+impl fmt::Display for Tree {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.__fmt_node(0, f).map(|_| ())
+    }
+}
+
+/*  Functions and terminals (input variables, and ephemeral random constants in the given range)
+ *  the trees are made of.
+ *  Trees are encoded in variable-length genomes, one gene per node in prefix order choosing its
+ *  symbol, a constant being followed by a gene holding its value. Any genome decodes to a valid
+ *  tree: missing arguments at the end of the genome are the first terminal, and the genes after
+ *  a complete tree are ignored. */
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct PrimitiveSet {
+    pub functions: Vec<GpFunction>,
+    pub nvars: usize,
+    pub constants: Option<(f64, f64)>,
+}
+
+impl PrimitiveSet {
+    pub fn validate(&self) -> Result<(), Errcode> {
+        if self.nterminals() == 0 {
+            return Err(Errcode::ValidationError("primitive set without terminals"));
+        }
+        if let Some((min, max)) = self.constants {
+            if min.is_nan() || max.is_nan() || (min > max) {
+                return Err(Errcode::ValidationError("primitive set constants range"));
+            }
+        }
+        Ok(())
+    }
+
+    fn nterminals(&self) -> usize {
+        self.nvars + if self.constants.is_some() { 1 } else { 0 }
+    }
+
+    fn nsymbols(&self) -> usize {
+        self.functions.len() + self.nterminals()
+    }
+
+    fn symbol_index(&self, node: &GpNode) -> usize {
+        match node {
+            GpNode::Func(f) => self.functions.iter().position(|func| func == f).unwrap_or(0),
+            GpNode::Var(n) => self.functions.len() + (*n).min(self.nvars.saturating_sub(1)),
+            GpNode::Const(_) => self.functions.len() + self.nvars,
+        }
+    }
+
+    fn const_to_gene(&self, val: f64) -> f64 {
+        match self.constants {
+            Some((min, max)) if max > min => ((val - min) / (max - min)).clamp(0.0, 1.0),
+            _ => 0.0,
+        }
+    }
+
+    fn const_from_gene(&self, gene: f64) -> f64 {
+        let (min, max) = self.constants.unwrap_or((0.0, 0.0));
+        min + gene.clamp(0.0, 1.0) * (max - min)
+    }
+
+    pub fn encode(&self, tree: &Tree) -> Genome {
+        let nsymbols = self.nsymbols() as f64;
+        let mut genome = Genome::with_capacity(tree.size());
+        for node in tree.nodes.iter() {
+            genome.push((self.symbol_index(node) as f64 + 0.5) / nsymbols);
+            if let GpNode::Const(c) = node {
+                genome.push(self.const_to_gene(*c));
+            }
+        }
+        genome
+    }
+
+    pub fn decode(&self, genome: &Genome) -> Tree {
+        let nfuncs = self.functions.len();
+        let mut nodes = vec![];
+        let mut missing = 1;
+        let mut n = 0;
+        while missing > 0 {
+            let node = match genome.get(n) {
+                None => self.__first_terminal(),
+                Some(g) => {
+                    let sym = ((g.clamp(0.0, 1.0) * (self.nsymbols() as f64)) as usize).min(self.nsymbols() - 1);
+                    if sym < nfuncs {
+                        GpNode::Func(self.functions[sym])
+                    } else if sym - nfuncs < self.nvars {
+                        GpNode::Var(sym - nfuncs)
+                    } else {
+                        n += 1;
+                        GpNode::Const(self.const_from_gene(genome.get(n).copied().unwrap_or(0.5)))
+                    }
+                }
+            };
+            n += 1;
+            missing = missing - 1 + node.arity();
+            nodes.push(node);
+        }
+        Tree { nodes }
+    }
+
+    fn __first_terminal(&self) -> GpNode {
+        if self.nvars > 0 {
+            GpNode::Var(0)
+        } else {
+            GpNode::Const(self.const_from_gene(0.5))
+        }
+    }
+
+    pub fn random_terminal(&self, rng: &mut ThreadRng) -> GpNode {
+        let n = rng.gen_range(0..self.nterminals());
+        if n < self.nvars {
+            GpNode::Var(n)
+        } else {
+            GpNode::Const(self.const_from_gene(rng.gen()))
+        }
+    }
+
+    /*  Full trees have all their leaves at the given depth, grown ones pick among all the
+     *  symbols at each node, so their branches can end earlier */
+    pub fn random_tree(&self, depth: usize, full: bool, rng: &mut ThreadRng) -> Tree {
+        let mut nodes = vec![];
+        self.__grow(depth, full, rng, &mut nodes);
+        Tree { nodes }
+    }
+
+    fn __grow(&self, depth: usize, full: bool, rng: &mut ThreadRng, nodes: &mut Vec<GpNode>) {
+        let terminal = (depth <= 1) || self.functions.is_empty()
+            || (!full && (rng.gen_range(0..self.nsymbols()) < self.nterminals()));
+        if terminal {
+            nodes.push(self.random_terminal(rng));
+            return;
+        }
+        let func = *self.functions.choose(rng).unwrap();
+        nodes.push(GpNode::Func(func));
+        for _ in 0..func.arity() {
+            self.__grow(depth - 1, full, rng, nodes);
+        }
+    }
+
+    // Half full and half grown trees, with depths spread over the given range
+    pub fn ramped_half_and_half(&self, n: usize, min_depth: usize, max_depth: usize, rng: &mut ThreadRng) -> Vec<Tree> {
+        let ndepths = max_depth.saturating_sub(min_depth) + 1;
+        (0..n).map(|i| self.random_tree(min_depth + (i / 2) % ndepths, i % 2 == 0, rng)).collect()
+    }
+
+    // A node replaced by a symbol of the same arity
+    pub fn point_mutation(&self, tree: &mut Tree, rng: &mut ThreadRng) {
+        let n = rng.gen_range(0..tree.size());
+        tree.nodes[n] = match tree.nodes[n] {
+            GpNode::Func(f) => {
+                let same_arity: Vec<&GpFunction> = self.functions.iter().filter(|g| g.arity() == f.arity()).collect();
+                GpNode::Func(**same_arity.choose(rng).unwrap_or(&&f))
+            }
+            _ => self.random_terminal(rng),
+        };
+    }
+
+    // A subtree replaced by a random grown tree of at most the given depth
+    pub fn subtree_mutation(&self, tree: &mut Tree, max_depth: usize, rng: &mut ThreadRng) {
+        let start = rng.gen_range(0..tree.size());
+        let end = tree.subtree_end(start);
+        let new = self.random_tree(rng.gen_range(1..=max_depth.max(1)), false, rng);
+        tree.nodes.splice(start..end, new.nodes);
+    }
+}
+
+// A subtree of the first parent replaced by a subtree of the second one
+pub fn subtree_crossover(p1: &Tree, p2: &Tree, rng: &mut ThreadRng) -> Tree {
+    let (start1, start2) = (rng.gen_range(0..p1.size()), rng.gen_range(0..p2.size()));
+    let (end1, end2) = (p1.subtree_end(start1), p2.subtree_end(start2));
+    let mut nodes = p1.nodes[..start1].to_vec();
+    nodes.extend_from_slice(&p2.nodes[start2..end2]);
+    nodes.extend_from_slice(&p1.nodes[end1..]);
+    Tree { nodes }
+}
+
+#[test]
+fn test_gp_trees() {
+    use GpNode::*;
+    let mut rng = rand::thread_rng();
+    let set = PrimitiveSet {
+        functions: vec![GpFunction::Add, GpFunction::Mul, GpFunction::Sin],
+        nvars: 2,
+        constants: Some((-1.0, 1.0)),
+    };
+
+    // (x0 * x0) + sin(0.5)
+    let tree = Tree { nodes: vec![Func(GpFunction::Add), Func(GpFunction::Mul), Var(0), Var(0),
+        Func(GpFunction::Sin), Const(0.5)] };
+    assert_eq!(tree.to_string(), "((x0 * x0) + sin(0.5000))");
+    assert_eq!((tree.depth(), tree.subtree_end(1), tree.subtree_end(4)), (3, 4, 6));
+    assert!((tree.eval(&[3.0]) - (9.0 + 0.5f64.sin())).abs() < 1e-9);
+    let genome = set.encode(&tree);
+    assert_eq!(genome.len(), 7);
+    assert_eq!(set.decode(&genome).to_string(), tree.to_string());
+    // Missing arguments are the first variable, extra genes ignored
+    assert_eq!(set.decode(&vec![0.1]).to_string(), "(x0 + x0)");
+    assert_eq!(set.decode(&vec![0.4, 0.7, 0.2]).to_string(), "sin(x1)");
+
+    for tree in set.ramped_half_and_half(50, 2, 5, &mut rng) {
+        assert!((tree.depth() <= 5) && (tree.subtree_end(0) == tree.size()));
+        let mut child = subtree_crossover(&tree, &Tree { nodes: vec![Var(1)] }, &mut rng);
+        set.point_mutation(&mut child, &mut rng);
+        set.subtree_mutation(&mut child, 3, &mut rng);
+        assert_eq!(child.subtree_end(0), child.size());
+        assert_eq!(set.decode(&set.encode(&child)).size(), child.size());
+    }
+    assert!(PrimitiveSet { functions: vec![GpFunction::Add], nvars: 0, constants: None }.validate().is_err());
+}
@@ -0,0 +1,155 @@
+use crate::algo::Algo;
+use crate::cell::{Cell, CellData, Genome};
+use crate::dataset::GenalgoData;
+use crate::errors::Errcode;
+use crate::gp::{PrimitiveSet, Tree};
+use crate::lab::Lab;
+use crate::utils::JsonData;
+
+use serde_json::json;
+use std::marker::PhantomData;
+
+/*  Symbolic regression problem: the rows of the datasets are the input variables followed by
+ *  the target, the trees are built from the primitive set of the spec */
+pub trait GpSpec: 'static {
+    fn primitive_set() -> PrimitiveSet;
+
+    // Longest genome, bounding the size of the trees
+    fn max_genome_length() -> usize {
+        256
+    }
+
+    // Score added per node of the tree, against bloat
+    fn parsimony() -> f64 {
+        0.0
+    }
+}
+
+/*  Cell scored with the mean squared error of its tree over the rows it evaluated, plus the
+ *  parsimony penalty, to minimize */
+pub struct SymbolicRegressionCell<S: GpSpec> {
+    celldata: CellData,
+    tree: Tree,
+    sqerr: f64,
+    nrows: usize,
+    _spec: PhantomData<S>,
+}
+
+impl<S: GpSpec> SymbolicRegressionCell<S> {
+    pub fn new(genome: &Genome) -> SymbolicRegressionCell<S> {
+        SymbolicRegressionCell {
+            celldata: CellData { genome: genome.clone(), score: 0.0, version: Self::genome_version() },
+            tree: S::primitive_set().decode(genome),
+            sqerr: 0.0,
+            nrows: 0,
+            _spec: PhantomData,
+        }
+    }
+
+    pub fn get_tree(&self) -> &Tree {
+        &self.tree
+    }
+}
+
+impl<S: GpSpec> Cell for SymbolicRegressionCell<S> {
+    type Data = GenalgoData;
+
+    // Length of the random genomes the lab starts from, decoded as small trees
+    fn get_genome_length() -> usize {
+        1
+    }
+
+    fn genome_length_bounds() -> Option<(usize, usize)> {
+        Some((1, S::max_genome_length()))
+    }
+
+    fn primitive_set() -> Option<PrimitiveSet> {
+        Some(S::primitive_set())
+    }
+
+    fn get_data(&self) -> &CellData {
+        &self.celldata
+    }
+
+    fn action(&mut self, data: &GenalgoData) {
+        let target = match data.last() {
+            Some(t) => *t,
+            None => return,
+        };
+        let err = self.tree.eval(&data[..data.len() - 1]) - target;
+        self.sqerr += err * err;
+        self.nrows += 1;
+        let score = (self.sqerr / (self.nrows as f64)) + (S::parsimony() * (self.tree.size() as f64));
+        self.celldata.score = if score.is_finite() { score } else { f64::MAX };
+    }
+
+    fn reset(&mut self, genome: &Genome) {
+        self.celldata.genome = genome.clone();
+        self.celldata.score = 0.0;
+        self.tree = S::primitive_set().decode(genome);
+        self.sqerr = 0.0;
+        self.nrows = 0;
+    }
+
+    fn genome_version_adapt(genome: &Genome, _version: u64) -> Genome {
+        genome.clone()
+    }
+}
+
+pub struct SymbolicRegressionAlgo<S: GpSpec> {
+    _spec: PhantomData<S>,
+}
+
+impl<S: GpSpec> Algo for SymbolicRegressionAlgo<S> {
+    type CellType = SymbolicRegressionCell<S>;
+
+    fn new() -> Self where Self: Sized {
+        SymbolicRegressionAlgo { _spec: PhantomData }
+    }
+
+    // Genomes as the nodes of the tree, along with the expression
+    fn genome_from_json(&self, jsdata: JsonData) -> Result<Genome, Errcode> {
+        let data: serde_json::Value = serde_json::from_str(&jsdata)?;
+        match data.get("tree") {
+            Some(nodes) => Ok(S::primitive_set().encode(&Tree { nodes: serde_json::from_value(nodes.clone())? })),
+            None => Err(Errcode::GenomeKeyDoesntExist("tree".to_string())),
+        }
+    }
+
+    fn genome_to_json(&self, genome: Genome) -> Result<JsonData, Errcode> {
+        let tree = S::primitive_set().decode(&genome);
+        Ok(serde_json::to_string(&json!({"expression": tree.to_string(), "tree": tree.nodes}))?)
+    }
+
+    fn initialize_cells(&mut self, _pop: &mut Vec<Self::CellType>) {}
+
+    fn create_cell_from_genome(&self, genome: &Genome) -> Self::CellType {
+        SymbolicRegressionCell::new(genome)
+    }
+
+    fn recv_special_data(&mut self, _data: &serde_json::Value) -> Result<(), JsonData> {
+        Ok(())
+    }
+
+    // "predict" method: value of the tree of a genome for the given inputs
+    fn send_special_data(&self, params: &serde_json::Value) -> Result<JsonData, JsonData> {
+        if params.get("method").and_then(|m| m.as_str()) != Some("predict") {
+            return Err("Please specify method: predict".to_string());
+        }
+        let genome: Genome = serde_json::from_value(params["genome"].clone()).map_err(|e| e.to_string())?;
+        let inputs: Vec<f64> = serde_json::from_value(params["inputs"].clone()).map_err(|e| e.to_string())?;
+        Ok(json!({"result": S::primitive_set().decode(&genome).eval(&inputs)}).to_string())
+    }
+
+    fn process_data(&mut self, pop: &mut Vec<Self::CellType>, data: &GenalgoData) {
+        for cell in pop.iter_mut() {
+            cell.action(data);
+        }
+    }
+
+    fn check_generation_over(&self, _genalgo: &Lab<Self::CellType>) -> bool {
+        true
+    }
+
+    fn reset(&mut self) {}
+}
@@ -0,0 +1,93 @@
+use crate::algo::AlgoID;
+use crate::cell::CellData;
+use crate::diversity::distance;
+use crate::errors::Errcode;
+use crate::utils::JsonData;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HallOfFameEntry {
+    pub cell: CellData,
+    pub algo: AlgoID,
+    pub generation: usize,
+}
+
+/*  Best distinct cells ever seen in the lab, best first. Two cells closer than
+ *  min_distance are the same one, only the better of both is kept, with its origin. */
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HallOfFame {
+    capacity: usize,
+    min_distance: f64,
+    maximize: bool,
+    entries: Vec<HallOfFameEntry>,
+}
+
+impl HallOfFame {
+    pub fn new(capacity: usize, min_distance: f64, maximize: bool) -> HallOfFame {
+        HallOfFame {
+            capacity,
+            min_distance,
+            maximize,
+            entries: vec![],
+        }
+    }
+
+    // Returns true if the cell entered the hall of fame
+    pub fn insert(&mut self, cell: &CellData, algo: AlgoID, generation: usize) -> bool {
+        if self.entries.len() == self.capacity {
+            match self.entries.last() {
+                Some(worst) if !self.is_better(cell, &worst.cell) => return false,
+                None => return false,
+                _ => {}
+            }
+        }
+        if let Some(n) = self.entries.iter().position(|e| distance(&e.cell.genome, &cell.genome) < self.min_distance) {
+            if !self.is_better(cell, &self.entries[n].cell) {
+                return false;
+            }
+            self.entries[n] = HallOfFameEntry { cell: cell.clone(), algo, generation };
+        } else {
+            self.entries.push(HallOfFameEntry { cell: cell.clone(), algo, generation });
+        }
+        let maximize = self.maximize;
+        self.entries.sort_by(|a, b| if maximize {
+            b.cell.score.partial_cmp(&a.cell.score).unwrap()
+        } else {
+            a.cell.score.partial_cmp(&b.cell.score).unwrap()
+        });
+        self.entries.truncate(self.capacity);
+        true
+    }
+
+    pub fn get_entries(&self) -> &[HallOfFameEntry] {
+        &self.entries
+    }
+
+    pub fn best(&self) -> Option<&HallOfFameEntry> {
+        self.entries.first()
+    }
+
+    pub fn to_json(&self) -> Result<JsonData, Errcode> {
+        Ok(serde_json::to_string(&self.entries)?)
+    }
+
+    fn is_better(&self, a: &CellData, b: &CellData) -> bool {
+        if self.maximize { a.score > b.score } else { a.score < b.score }
+    }
+}
+
+#[test]
+fn test_hall_of_fame() {
+    let cell = |genome: f64, score: f64| CellData { genome: vec![genome], score, version: 0 };
+    let mut hof = HallOfFame::new(2, 1e-9, false);
+    assert!(hof.insert(&cell(0.1, 3.0), 0, 0));
+    assert!(hof.insert(&cell(0.2, 2.0), 1, 0));
+    assert!(!hof.insert(&cell(0.3, 4.0), 0, 1));
+    assert!(!hof.insert(&cell(0.1, 3.5), 0, 1));
+    assert!(hof.insert(&cell(0.1, 1.0), 1, 2));
+    let entries = hof.get_entries();
+    assert_eq!(entries.len(), 2);
+    assert_eq!((entries[0].cell.score, entries[0].algo, entries[0].generation), (1.0, 1, 2));
+    assert_eq!(entries[1].cell.genome, vec![0.2]);
+}
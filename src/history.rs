@@ -0,0 +1,102 @@
+use crate::algo::AlgoID;
+use crate::cell::{CellData, Score};
+use crate::diversity::DiversityMeasures;
+use crate::errors::Errcode;
+use crate::utils::JsonData;
+
+use serde::{Deserialize, Serialize};
+
+/*  Scores of the output algo along a run: its best training score each generation, and
+ *  the best validation score of its elites when they were scored on the validation data.
+ *  The validation scores never take part in the selection of the cells.
+ *  The diversity of the output algo population is recorded each generation, and the
+ *  restarts of every algo when they happen. */
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct RunHistory {
+    pub training: Vec<(usize, Score)>,
+    pub validation: Vec<(usize, Score)>,
+    pub best_validation: Option<(usize, CellData)>,
+    pub diversity: Vec<(usize, DiversityMeasures)>,
+    pub restarts: Vec<(usize, AlgoID)>,
+    maximize_score: bool,
+}
+
+impl RunHistory {
+    pub fn new(maximize_score: bool) -> RunHistory {
+        RunHistory {
+            maximize_score,
+            ..RunHistory::default()
+        }
+    }
+
+    pub fn add_training(&mut self, generation: usize, score: Score) {
+        self.training.push((generation, score));
+    }
+
+    pub fn add_diversity(&mut self, generation: usize, measures: DiversityMeasures) {
+        self.diversity.push((generation, measures));
+    }
+
+    pub fn add_restart(&mut self, generation: usize, algo: AlgoID) {
+        self.restarts.push((generation, algo));
+    }
+
+    pub fn add_validation(&mut self, generation: usize, cells: Vec<CellData>) {
+        let best = match cells.into_iter().fold(None, |best: Option<CellData>, c| match best {
+            Some(b) if !self.is_better(c.score, b.score) => Some(b),
+            _ => Some(c),
+        }) {
+            Some(b) => b,
+            None => return,
+        };
+        self.validation.push((generation, best.score));
+        let improved = match &self.best_validation {
+            Some((_, b)) => self.is_better(best.score, b.score),
+            None => true,
+        };
+        if improved {
+            self.best_validation = Some((generation, best));
+        }
+    }
+
+    /*  Overfitting: the validation score didn't improve during the last `patience`
+     *  validations, while the training score kept improving meanwhile. */
+    pub fn is_overfitting(&self, patience: usize) -> bool {
+        let best_gen = match &self.best_validation {
+            Some((gen, _)) => *gen,
+            None => return false,
+        };
+        let nb_since_best = self.validation.iter().filter(|(gen, _)| *gen > best_gen).count();
+        if patience == 0 || nb_since_best < patience {
+            return false;
+        }
+        let training_at = |gen: usize| self.training.iter().rev().find(|(g, _)| *g <= gen).map(|t| t.1);
+        match (training_at(best_gen), self.training.last()) {
+            (Some(at_best), Some((_, last))) => self.is_better(*last, at_best),
+            _ => false,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<JsonData, Errcode> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    fn is_better(&self, a: Score, b: Score) -> bool {
+        if self.maximize_score { a > b } else { a < b }
+    }
+}
+
+#[test]
+fn test_run_history_overfitting() {
+    let cell = |score: Score| CellData { genome: vec![], score, version: 0 };
+    let mut history = RunHistory::new(false);
+    for gen in 0..6 {
+        history.add_training(gen, 10.0 - (gen as f64));
+        let val = if gen < 2 { 10.0 - (gen as f64) } else { 9.0 + (gen as f64) };
+        history.add_validation(gen, vec![cell(val + 1.0), cell(val)]);
+    }
+    assert_eq!(history.validation[1], (1, 9.0));
+    assert_eq!(history.best_validation.as_ref().unwrap().0, 1);
+    assert!(history.is_overfitting(3));
+    assert!(!history.is_overfitting(5));
+}
@@ -1,8 +1,18 @@
-use crate::algo::{Algo, AlgoConfiguration, AlgoID, AlgoResult};
-use crate::cell::{random_genome, Cell, CellData, Genome};
+use crate::algo::{evaluate_genomes, Algo, AlgoConfiguration, AlgoID, AlgoResult};
+use crate::cell::{genome_length_is_valid, max_genome_length, random_genome, Cell, CellData, Genome, Score};
 use crate::dataset::DatasetHandler;
+use crate::diversity::DiversityMeasures;
 use crate::errors::Errcode;
 use crate::genalgomethods::{GenalgoMethod, GenalgoMethodsAvailable};
+use crate::cache::{EvalCache, EvalCacheConfig, EvalCacheStats};
+use crate::halloffame::{HallOfFame, HallOfFameEntry};
+use crate::history::RunHistory;
+use crate::seeding::{seed_population, SeedFill};
+use crate::restart::{best_archived, genomes_around, score_is_better, ArchivedCell, RestartConfig,
+    RestartPolicy, RestartState};
+use crate::labmaps::graph::MapGraph;
+use crate::migration::{GenomeAdapter, GenomeMigrations};
+use crate::population::plan_populations;
 use crate::utils::{JsonData, MeanCompute};
 
 use serde::{Deserialize, Serialize};
@@ -15,6 +25,24 @@ pub struct LabConfig {
     pub npop: usize,
     pub elite_ratio: f64,
     pub maximize_score: bool,
+    #[serde(default = "default_validation_interval")]
+    pub validation_interval: usize,     // Generations between two validations, 0 to disable
+    #[serde(default)]
+    pub select_by_validation: bool,     // Return the best cell on the validation data
+    #[serde(default)]
+    pub eval_cache: Option<EvalCacheConfig>,    // For deterministic fitness only
+    #[serde(default)]
+    pub restart: Option<RestartConfig>,         // Restart of the stagnating algos
+    #[serde(default = "default_hall_of_fame_size")]
+    pub hall_of_fame_size: usize,
+}
+
+fn default_validation_interval() -> usize {
+    1
+}
+
+fn default_hall_of_fame_size() -> usize {
+    10
 }
 
 impl LabConfig {
@@ -23,6 +51,11 @@ impl LabConfig {
             npop: 1000,
             elite_ratio: 0.1,
             maximize_score: false,
+            validation_interval: default_validation_interval(),
+            select_by_validation: false,
+            eval_cache: None,
+            restart: None,
+            hall_of_fame_size: default_hall_of_fame_size(),
         }
     }
 
@@ -31,6 +64,11 @@ impl LabConfig {
             npop: npop,
             elite_ratio: elite_ratio,
             maximize_score: maximize_score,
+            validation_interval: default_validation_interval(),
+            select_by_validation: false,
+            eval_cache: None,
+            restart: None,
+            hall_of_fame_size: default_hall_of_fame_size(),
         }
     }
 
@@ -43,6 +81,8 @@ impl LabConfig {
     }
 }
 
+const HALL_OF_FAME_MIN_DISTANCE: f64 = 1e-9;
+
 pub struct Lab<T: Cell> {
     genalgo_methods: Vec<Box<dyn GenalgoMethod<T>>>,
 
@@ -57,6 +97,17 @@ pub struct Lab<T: Cell> {
     algo_configs_set: bool,
 
     mean_calc: MeanCompute,
+    history: RunHistory,
+    cache: Option<EvalCache>,
+    datasets_identity: Vec<String>,
+    diversity: Vec<DiversityMeasures>,
+    restarts: Vec<RestartState>,
+    archive: Vec<ArchivedCell>,
+    best_cell: Option<CellData>,
+    hall_of_fame: HallOfFame,
+    populations: Vec<(usize, usize)>, // Planned population and elites of each algo
+    migrations: GenomeMigrations,
+    seeds: Vec<Option<(Vec<Genome>, SeedFill)>>,
 }
 
 impl<T: 'static + Cell> Lab<T> {
@@ -74,9 +125,31 @@ impl<T: 'static + Cell> Lab<T> {
             init_done: false,
             algo_configs_set: false,
             mean_calc: MeanCompute::new(),
+            history: RunHistory::default(),
+            cache: None,
+            datasets_identity: vec![],
+            diversity: vec![],
+            restarts: vec![],
+            archive: vec![],
+            best_cell: None,
+            hall_of_fame: HallOfFame::new(config.hall_of_fame_size, HALL_OF_FAME_MIN_DISTANCE,
+                config.maximize_score),
+            populations: vec![],
+            migrations: GenomeMigrations::default(),
+            seeds: vec![],
         }
     }
 
+    pub fn set_config(&mut self, config: LabConfig) -> Result<(), Errcode> {
+        if self.init_done {
+            return Err(Errcode::ValidationError("lab config, lab already initialized"));
+        }
+        self.config = config;
+        self.hall_of_fame = HallOfFame::new(config.hall_of_fame_size, HALL_OF_FAME_MIN_DISTANCE,
+            config.maximize_score);
+        Ok(())
+    }
+
     pub fn apply_map_with_algo<A: 'static + Algo<CellType = T>>(
         &mut self, map: Vec<AlgoConfiguration>) -> Result<(), Errcode>{
 
@@ -94,6 +167,7 @@ impl<T: 'static + Cell> Lab<T> {
         self.__validate_map(&map)?;
         self.configs = map.clone();
         self.algo_configs_set = true;
+        self.clear_cache();
         Ok(())
     }
 
@@ -104,6 +178,9 @@ impl<T: 'static + Cell> Lab<T> {
         self.configs.push(AlgoConfiguration::default());
         self.bestgens.push(random_genome(T::get_genome_length()));
         self.cells.push(vec![]);
+        self.diversity.push(DiversityMeasures::default());
+        self.restarts.push(RestartState::default());
+        self.seeds.push(None);
         self.algos.push(algo);
         Ok(self.algos.len() - 1)
     }
@@ -121,14 +198,18 @@ impl<T: 'static + Cell> Lab<T> {
             return Err(e);
         }
         self.algo_configs_set = true;
+        self.clear_cache();
         Ok(())
     }
 
+    // Genomes of an older version are migrated through the registered adapters
     pub fn import_best_genome(
         &mut self,
         genome: Genome,
+        version: u64,
         algo_id: Option<AlgoID>,
     ) -> Result<(), Errcode> {
+        let genome = self.migrations.migrate::<T>(&genome, version)?;
         if let Some(id) = algo_id {
             self.__check_id_exist(id)?;
             self.bestgens[id] = genome;
@@ -140,6 +221,62 @@ impl<T: 'static + Cell> Lab<T> {
         Ok(())
     }
 
+    /*  Seed genomes placed in the initial population of the algo (of all algos if None),
+     *  the rest of the population being filled as set. Genomes without version are
+     *  considered of the current one. If the lab already started, its population is replaced. */
+    pub fn import_seed_genomes(
+        &mut self,
+        seeds: Vec<(Genome, Option<u64>)>,
+        fill: SeedFill,
+        algo_id: Option<AlgoID>,
+    ) -> Result<(), Errcode> {
+        fill.validate()?;
+        let mut genomes = vec![];
+        for (genome, version) in seeds.iter() {
+            genomes.push(self.migrations.migrate::<T>(genome, version.unwrap_or_else(T::genome_version))?);
+        }
+        let ids = match algo_id {
+            Some(id) => {
+                self.__check_id_exist(id)?;
+                vec![id]
+            }
+            None => (0..self.algos.len()).collect(),
+        };
+        for id in ids {
+            self.seeds[id] = Some((genomes.clone(), fill));
+            if self.init_done {
+                let genomes = self.__seeded_genomes(id).unwrap();
+                self.__set_population(id, &genomes);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn register_genome_adapter(&mut self, from_version: u64, adapter: GenomeAdapter) {
+        self.migrations.register(from_version, adapter);
+    }
+
+    // Cells of a previous run, migrated to the current genome version
+    pub fn import_hall_of_fame(&mut self, entries: &[HallOfFameEntry]) -> Result<(), Errcode> {
+        for entry in entries.iter() {
+            let cell = self.migrations.migrate_cell::<T>(&entry.cell)?;
+            self.hall_of_fame.insert(&cell, entry.algo, entry.generation);
+        }
+        Ok(())
+    }
+
+    pub fn get_map(&self) -> &Vec<AlgoConfiguration> {
+        &self.configs
+    }
+
+    pub fn get_config(&self) -> &LabConfig {
+        &self.config
+    }
+
+    pub fn export_map_graph(&self) -> MapGraph {
+        MapGraph::new(&self.configs, Some(&self.config), self.out_algo)
+    }
+
     pub fn export_best_genome(&mut self) -> Result<Genome, Errcode> {
         if let Some(id) = self.out_algo {
             Ok(self.bestgens[id].clone())
@@ -154,6 +291,8 @@ impl<T: 'static + Cell> Lab<T> {
         data: &serde_json::Value,
     ) -> Result<(), Errcode> {
         self.__check_id_exist(id)?;
+        // The data may change the fitness of the algo
+        self.clear_cache();
         match self.algos[id].recv_special_data(data) {
             Ok(_) => Ok(()),
             Err(e) => Err(Errcode::SpecialDataError(e)),
@@ -172,23 +311,87 @@ impl<T: 'static + Cell> Lab<T> {
         }
     }
 
+    // Ids of the datasets the cells are scored on, the cached scores depend on them
+    pub fn set_datasets_identity(&mut self, ids: Vec<String>) {
+        self.datasets_identity = ids;
+    }
+
+    // Cached scores are only valid as long as the datasets they were obtained on don't change
+    pub fn clear_cache(&mut self) {
+        if let Some(cache) = self.cache.as_mut() {
+            cache.clear();
+        }
+    }
+
+    pub fn get_cache_stats(&self) -> Option<EvalCacheStats> {
+        self.cache.as_ref().map(|c| c.get_stats())
+    }
+
+    // Diversity of the algo population at the last generation
+    pub fn get_diversity(&self, id: AlgoID) -> Result<&DiversityMeasures, Errcode> {
+        match self.diversity.get(id) {
+            Some(d) => Ok(d),
+            None => Err(Errcode::IdDoesntExist(id)),
+        }
+    }
+
+    pub fn get_restart_counts(&self) -> Vec<usize> {
+        self.restarts.iter().map(|r| r.nrestarts).collect()
+    }
+
+    // Best cells of the algos before each of their restarts
+    pub fn get_restart_archive(&self) -> &[ArchivedCell] {
+        &self.archive
+    }
+
+    // Best distinct cells seen in all the algos since the lab creation
+    pub fn get_hall_of_fame(&self) -> &HallOfFame {
+        &self.hall_of_fame
+    }
+
+    pub fn get_history(&self) -> &RunHistory {
+        &self.history
+    }
+
     pub fn start(
         &mut self,
         ngeneration: usize,
-        datasets: &mut Vec<Box<dyn DatasetHandler>>,
+        datasets: &mut Vec<Box<dyn DatasetHandler<T::Data>>>,
+        validation: &mut Vec<Box<dyn DatasetHandler<T::Data>>>,
     ) -> Result<CellData, Errcode> {
 
         self.__validate_configuration()?;
         self.__init_lab()?;
+        self.history = RunHistory::new(self.config.maximize_score);
+        if let Some(cache) = self.cache.as_mut() {
+            cache.set_datasets(&self.datasets_identity);
+        }
+        let validate = !validation.is_empty() && self.config.validation_interval > 0;
         let mut top_cell: Option<CellData> = Option::None;
-        for _ in 0..ngeneration {
+        for gen in 0..ngeneration {
             let t = SystemTime::now();
-            top_cell = Some(self.__loop_gen(datasets)?);
+            let (cell, elites) = self.__loop_gen(gen, datasets)?;
+            self.history.add_training(gen, cell.score);
+            self.history.add_diversity(gen, self.diversity[self.out_algo.unwrap()].clone());
+            if validate && ((gen + 1) % self.config.validation_interval == 0 || gen + 1 == ngeneration) {
+                self.__validate_elites(gen, &elites, validation);
+            }
+            top_cell = Some(cell);
             self.mean_calc
                 .add_el(t.elapsed().unwrap().as_secs_f64(), 1.0);
             //println!("avg generation time: {}", self.mean_calc.result);
         }
-        Ok(top_cell.unwrap())
+        if self.config.select_by_validation {
+            if let Some((_, best)) = &self.history.best_validation {
+                return Ok(best.clone());
+            }
+        }
+        let top_cell = top_cell.unwrap();
+        let maximize = self.config.maximize_score;
+        match best_archived(&self.archive, self.out_algo.unwrap(), maximize) {
+            Some(archived) if score_is_better(archived.score, top_cell.score, maximize) => Ok(archived.clone()),
+            _ => Ok(top_cell),
+        }
     }
 
     /*              INTERNALS               */
@@ -202,6 +405,7 @@ impl<T: 'static + Cell> Lab<T> {
                     match self.genalgo_methods.get_mut(method_enum_n as usize) {
                         Some(m) => {
                             m.load_config(&cfg.method_options);
+                            m.set_init_strategy(cfg.init);
                             Ok(m)
                         }
                         None => {
@@ -225,39 +429,133 @@ impl<T: 'static + Cell> Lab<T> {
         Ok(())
     }
 
+    // Returns the best cell of the output algo, and the genomes of its elites
     fn __loop_gen(
         &mut self,
-        datasets: &mut Vec<Box<dyn DatasetHandler>>,
-    ) -> Result<CellData, Errcode> {
+        gen: usize,
+        datasets: &mut Vec<Box<dyn DatasetHandler<T::Data>>>,
+    ) -> Result<(CellData, Vec<Genome>), Errcode> {
+        let use_cache = self.cache.is_some() && datasets.iter().all(|d| d.is_deterministic());
+        let mut cached = if use_cache {
+            self.__set_aside_cached_cells()
+        } else {
+            (0..self.algos.len()).map(|_| (vec![], vec![])).collect()
+        };
         for dataset in datasets.iter_mut() {
             self.__run_on_dataset(dataset)?;
         }
 
         let mut results: Vec<AlgoResult> = vec![];
         for id in 0..self.algos.len() {
-            results.push(AlgoResult::new(
-                self.configs
-                    .get(id)
-                    .unwrap()
-                    .get_pop_and_elite(self.config.npop, self.config.elite_ratio)
-                    .1,
-            ));
+            results.push(AlgoResult::new(self.__get_pop_and_elite(id).1));
         }
-        let mut top_cell: Option<CellData> = Option::None;
+        let reevaluate = datasets.iter().any(|d| d.reevaluate_elites());
+        let mut top_cell: Option<(CellData, Vec<Genome>)> = Option::None;
         for id in 0..self.algos.len() {
             results[id].load_cells(self.cells.get(id).unwrap());
+            if use_cache {
+                let cache = self.cache.as_mut().unwrap();
+                for cell in results[id].cells_data.iter() {
+                    cache.insert(id, &cell.genome, cell.score);
+                }
+                let (cells, scores) = std::mem::take(&mut cached[id]);
+                results[id].load_cached_cells(&cells, &scores);
+                self.cells[id].extend(cells);
+            }
             results[id].sort_cells(self.config.maximize_score)?;
+            if reevaluate {
+                self.__reevaluate_elites(id, &mut results[id], datasets)?;
+            }
+            // Archived and exported cells carry the genome version of the cell type
+            for cell in results[id].cells_data.iter_mut() {
+                cell.version = T::genome_version();
+            }
+            for cell in results[id].cells_data.iter().take(self.config.hall_of_fame_size) {
+                self.hall_of_fame.insert(cell, id, gen);
+            }
+            let best = results[id].cells_data[0].clone();
             if id == self.out_algo.unwrap() {
-                top_cell = Some(results[id].cells_data[0].clone());
+                top_cell = Some((best.clone(), results[id].get_top_genomes()));
             }
+            self.__preserve_diversity(id, &mut results[id]);
             self.__propagate_results(id, &mut results)?;
             self.__prepare_next_gen(id, &results[id])?;
             self.algos[id].reset();
+            if let Some(cfg) = self.config.restart {
+                self.__check_restart(id, gen, &best, &cfg)?;
+            }
         }
         Ok(top_cell.unwrap())
     }
 
-    fn __run_on_dataset(&mut self, dataset: &mut Box<dyn DatasetHandler>) -> Result<(), Errcode> {
+    fn __check_restart(&mut self, id: AlgoID, gen: usize, best: &CellData, cfg: &RestartConfig
+        ) -> Result<(), Errcode> {
+        let maximize = self.config.maximize_score;
+        #[allow(clippy::unnecessary_map_or)] // is_none_or needs Rust 1.82
+        let improved = self.best_cell.as_ref().map_or(true, |b| score_is_better(best.score, b.score, maximize));
+        if improved {
+            self.best_cell = Some(best.clone());
+        }
+        let pop = self.cells[id].len();
+        if !self.restarts[id].update(best, cfg, maximize, pop) {
+            return Ok(());
+        }
+        if let Some(archived) = self.restarts[id].restart(cfg, id, gen) {
+            self.archive.push(archived);
+        }
+        self.history.add_restart(gen, id);
+
+        let (pop, elite) = self.__get_pop_and_elite(id);
+        let genomes = match (cfg.policy, &self.best_cell) {
+            (RestartPolicy::AroundBest { sigma }, Some(best)) => genomes_around(&best.genome, sigma, pop),
+            _ => {
+                let mut genomes = vec![];
+                self.get_method_from_algo(id)?.init_population(&vec![], pop as u32, elite as u32,
+                    &mut genomes)?;
+                genomes
+            }
+        };
+        self.__set_population(id, &genomes);
+        Ok(())
+    }
+
+    // Population size grows with the IPOP / BIPOP restarts
+    fn __get_pop_and_elite(&self, id: AlgoID) -> (usize, usize) {
+        let (pop, elite) = self.populations[id];
+        let factor = self.restarts[id].pop_factor;
+        (((pop as f64) * factor) as usize, ((elite as f64) * factor) as usize)
+    }
+
+    fn __preserve_diversity(&mut self, id: AlgoID, result: &mut AlgoResult) {
+        let genomes: Vec<&Genome> = result.cells_data.iter().map(|c| &c.genome).collect();
+        self.diversity[id] = DiversityMeasures::compute(&genomes);
+        self.configs[id].diversity.reorder_cells(&mut result.cells_data, self.config.maximize_score);
+    }
+
+    // Cells already scored on the same datasets are left out of this generation evaluation
+    fn __set_aside_cached_cells(&mut self) -> Vec<(Vec<T>, Vec<Score>)> {
+        let cache = self.cache.as_mut().unwrap();
+        let mut res = vec![];
+        for (id, cells) in self.cells.iter_mut().enumerate() {
+            let mut evaluated = vec![];
+            let mut skipped = vec![];
+            let mut scores = vec![];
+            for cell in cells.drain(..) {
+                match cache.get(id, &cell.get_data().genome) {
+                    Some(score) => {
+                        skipped.push(cell);
+                        scores.push(score);
+                    }
+                    None => evaluated.push(cell),
+                }
+            }
+            *cells = evaluated;
+            res.push((skipped, scores));
+        }
+        res
+    }
+
+    fn __run_on_dataset(&mut self, dataset: &mut Box<dyn DatasetHandler<T::Data>>) -> Result<(), Errcode> {
         dataset.prepare();
         loop {
             let new_data_got = dataset.get_next_data();
@@ -272,6 +570,35 @@ impl<T: 'static + Cell> Lab<T> {
         Ok(())
     }
 
+    // Validation scores are only recorded, the populations are left untouched
+    fn __validate_elites(
+        &mut self,
+        gen: usize,
+        elites: &[Genome],
+        validation: &mut Vec<Box<dyn DatasetHandler<T::Data>>>,
+    ) {
+        let out = self.out_algo.unwrap();
+        let mut cells = evaluate_genomes(self.algos[out].as_mut(), elites, validation);
+        for cell in cells.iter_mut() {
+            cell.version = T::genome_version();
+        }
+        self.history.add_validation(gen, cells);
+    }
+
+    // The elites were scored on samples of the datasets, rank them on the whole datasets
+    fn __reevaluate_elites(
+        &mut self,
+        id: AlgoID,
+        result: &mut AlgoResult,
+        datasets: &mut Vec<Box<dyn DatasetHandler<T::Data>>>,
+    ) -> Result<(), Errcode> {
+        let genomes = result.get_top_genomes();
+        let scores: Vec<Score> = evaluate_genomes(self.algos[id].as_mut(), &genomes, datasets)
+            .iter().map(|c| c.score).collect();
+        result.rank_top_cells(&scores, self.config.maximize_score);
+        Ok(())
+    }
+
     fn __propagate_results(
         &mut self,
         id: AlgoID,
@@ -299,6 +626,8 @@ impl<T: 'static + Cell> Lab<T> {
             &res.cells_data,
             &mut genomes,
         )?;
+        let cfg = &self.configs[id];
+        cfg.diversity.process_genomes(&mut genomes, &cfg.impr_genes);
 
         if genomes.len() != self.cells.get(id).unwrap().len() {
             return Err(Errcode::CodeError(
@@ -323,14 +652,17 @@ impl<T: 'static + Cell> Lab<T> {
             return Ok(());
         }
         self.__init_genalgo_methods()?;
+        if let Some(cfg) = self.config.eval_cache {
+            self.cache = Some(EvalCache::new(cfg)?);
+        }
         for id in 0..self.algos.len() {
+            if let Some(genomes) = self.__seeded_genomes(id) {
+                self.__set_population(id, &genomes);
+                continue;
+            }
             let mut genomes = vec![];
 
-            let (pop, elite) = self
-                .configs
-                .get(id)
-                .unwrap()
-                .get_pop_and_elite(self.config.npop, self.config.elite_ratio);
+            let (pop, elite) = self.__get_pop_and_elite(id);
 
             let bestgens = self.bestgens.get(id).unwrap().clone();
             self.get_method_from_algo(id)?.init_population(
@@ -339,22 +671,24 @@ impl<T: 'static + Cell> Lab<T> {
                 elite as u32,
                 &mut genomes,
             )?;
-
-            for gene in genomes.iter() {
-                self.cells
-                    .get_mut(id)
-                    .unwrap()
-                    .push(self.algos.get(id).unwrap().create_cell_from_genome(gene));
-            }
-            self.algos
-                .get_mut(id)
-                .unwrap()
-                .initialize_cells(self.cells.get_mut(id).unwrap());
+            self.__set_population(id, &genomes);
         }
         self.init_done = true;
         Ok(())
     }
 
+    fn __seeded_genomes(&self, id: AlgoID) -> Option<Vec<Genome>> {
+        let (seeds, fill) = self.seeds[id].as_ref()?;
+        let (pop, _) = self.__get_pop_and_elite(id);
+        Some(seed_population::<T>(seeds, pop, fill))
+    }
+
+    fn __set_population(&mut self, id: AlgoID, genomes: &[Genome]) {
+        let cells = genomes.iter().map(|g| self.algos[id].create_cell_from_genome(g)).collect();
+        self.cells[id] = cells;
+        self.algos[id].initialize_cells(&mut self.cells[id]);
+    }
+
     fn __check_id_exist(&self, id: AlgoID) -> Result<(), Errcode> {
         if id < self.algos.len() {
             Ok(())
@@ -364,10 +698,6 @@ impl<T: 'static + Cell> Lab<T> {
     }
 
     fn __validate_configuration(&mut self) -> Result<(), Errcode> {
-        if self.config.npop < 100 {
-            return Err(Errcode::InsuffisantPopulation(self.config.npop, 100));
-        }
-        //TODO  Check if the sum of all algos = total population
         if self.algos.len() == 0 {
             return Err(Errcode::NotSet("lab algorithms"));
         }
@@ -389,6 +719,16 @@ impl<T: 'static + Cell> Lab<T> {
         for method in self.genalgo_methods.iter() {
             method.validate_config()?;
         }
+        if !genome_length_is_valid::<T>(T::get_genome_length()) {
+            return Err(Errcode::ValidationError("genome length out of the cell length bounds"));
+        }
+        for cfg in self.configs.iter() {
+            cfg.init.validate(max_genome_length::<T>())?;
+        }
+        self.populations = plan_populations(&self.configs, self.config.npop, self.config.elite_ratio)?;
+        if let Some(cfg) = self.config.restart {
+            cfg.validate()?;
+        }
         Ok(())
     }
 
@@ -416,3 +756,57 @@ impl<T: 'static + Cell> Lab<T> {
         println!("");
     }
 }
+
+#[test]
+fn test_eval_cache_per_algo() {
+    use crate::dataset::{EmptyDataset, GenalgoData};
+
+    crate::cell::test_cell!(FactorCell, GenalgoData, 2);
+    // Cells scored with 10 plus their first gene, times the factor of the algo
+    struct FactorAlgo {
+        factor: f64,
+    }
+    impl Algo for FactorAlgo {
+        type CellType = FactorCell;
+        fn new() -> Self { FactorAlgo { factor: 1.0 } }
+        fn genome_from_json(&self, _jsdata: JsonData) -> Result<Genome, Errcode> { Err(Errcode::NotImplemented("genome_from_json")) }
+        fn genome_to_json(&self, _genome: Genome) -> Result<JsonData, Errcode> { Err(Errcode::NotImplemented("genome_to_json")) }
+        fn initialize_cells(&mut self, _pop: &mut Vec<FactorCell>) {}
+        fn create_cell_from_genome(&self, genome: &Genome) -> FactorCell {
+            FactorCell { data: CellData { genome: genome.clone(), score: 0.0, version: 0 } }
+        }
+        fn recv_special_data(&mut self, data: &serde_json::Value) -> Result<(), JsonData> {
+            self.factor = data["factor"].as_f64().ok_or_else(|| "factor".to_string())?;
+            Ok(())
+        }
+        fn send_special_data(&self, _params: &serde_json::Value) -> Result<JsonData, JsonData> { Ok("".to_string()) }
+        fn process_data(&mut self, pop: &mut Vec<FactorCell>, _data: &GenalgoData) {
+            for cell in pop.iter_mut() {
+                cell.data.score = self.factor * (10.0 + cell.data.genome[0]);
+            }
+        }
+        fn check_generation_over(&self, _genalgo: &Lab<FactorCell>) -> bool { true }
+        fn reset(&mut self) {}
+    }
+
+    let mut config = LabConfig::new(200, 0.1, true);
+    config.eval_cache = Some(EvalCacheConfig::default());
+    let mut lab: Lab<FactorCell> = Lab::new(config);
+    lab.register_new_algo(Box::new(FactorAlgo { factor: 2.0 })).unwrap();
+    lab.register_new_algo(Box::new(FactorAlgo::new())).unwrap();
+    let mut map = vec![AlgoConfiguration::default(), AlgoConfiguration::default()];
+    map[0].id = "Doubled".to_string();
+    map[0].give = vec![1];
+    map[1].id = "Out".to_string();
+    lab.apply_map(map).unwrap();
+    lab.out_algo = Some(1);
+
+    // The cells given by the first algo are scored again by the second one
+    let mut datasets: Vec<Box<dyn DatasetHandler<GenalgoData>>> = vec![Box::new(EmptyDataset::new(1))];
+    let best = lab.start(3, &mut datasets, &mut vec![]).unwrap();
+    assert_eq!(best.score, 10.0 + best.genome[0]);
+    assert!(lab.get_cache_stats().unwrap().hits > 0);
+    lab.recv_special_data(1, &serde_json::json!({"factor": 0.5})).unwrap();
+    let best = lab.start(1, &mut datasets, &mut vec![]).unwrap();
+    assert_eq!(best.score, 0.5 * (10.0 + best.genome[0]));
+}
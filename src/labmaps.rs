@@ -1,6 +1,9 @@
 use crate::algo::{AlgoConfiguration, AlgoPopulation};
+use crate::diversity::DiversityPreservation;
 use crate::errors::Errcode;
-use crate::genalgomethods::GenalgoMethodsConfigurations;
+use crate::genalgomethods::{GenalgoMethodsAvailable, GenalgoMethodsConfigurations};
+use crate::sampling::InitStrategy;
+use crate::sensitivity::SensitivityAnalysis;
 use crate::utils::JsonData;
 use crate::*;
 
@@ -8,11 +11,23 @@ use enum_dispatch::enum_dispatch;
 use pyo3::prelude::*;
 mod wheel;
 use wheel::WheelFormat;
+mod ring;
+use ring::RingFormat;
+mod star;
+use star::StarFormat;
+mod grid;
+use grid::GridFormat;
+mod fully_connected;
+use fully_connected::FullyConnectedFormat;
+mod hierarchical;
+use hierarchical::HierarchicalFormat;
+pub mod mapfile;
+pub mod graph;
 
 #[enum_dispatch(LabMapFormatType)]
 trait LabMapFormat {
-    fn generate_map(&self, iso_algos: &Vec<AlgoConfiguration>,
-        random_opti: &AlgoConfiguration, final_tail: &Vec<AlgoConfiguration>,
+    fn generate_map(&self, iso_algos: &[AlgoConfiguration],
+        random_opti: Option<&AlgoConfiguration>, final_tail: &[AlgoConfiguration],
         priorities: &[f64; 3], mix_method: String) -> Result<JsonData, Errcode>;
 
 }
@@ -20,21 +35,112 @@ trait LabMapFormat {
 #[enum_dispatch]
 pub enum LabMapFormatType {
     WheelFormat,
+    RingFormat,
+    StarFormat,
+    GridFormat,
+    FullyConnectedFormat,
+    HierarchicalFormat,
 }
 
+type LabMapFormatCreator = fn() -> LabMapFormatType;
+
+const LAB_MAP_FORMATS: &[(&str, LabMapFormatCreator)] = &[
+    ("WheelFormat", || WheelFormat::new().into()),
+    ("RingFormat", || RingFormat.into()),
+    ("StarFormat", || StarFormat.into()),
+    ("GridFormat", || GridFormat.into()),
+    ("FullyConnectedFormat", || FullyConnectedFormat.into()),
+    ("HierarchicalFormat", || HierarchicalFormat.into()),
+];
+
 impl LabMapFormatType {
-    fn from(format_str: String) -> LabMapFormatType {
-        //TODO  Use enum to string mehod
-        match format_str.as_str() {
-            "WheelFormat" => WheelFormat::new().into(),
-            _ => unreachable!(),
+    pub fn from_name(format_str: &str) -> Result<LabMapFormatType, Errcode> {
+        match LAB_MAP_FORMATS.iter().find(|(name, _)| *name == format_str) {
+            Some((_, create)) => Ok(create()),
+            None => Err(Errcode::LabMapFormatDoesntExist(format_str.to_string(),
+                LabMapFormatType::available())),
         }
     }
+
+    pub fn available() -> Vec<&'static str> {
+        LAB_MAP_FORMATS.iter().map(|(name, _)| *name).collect()
+    }
+}
+
+/*          Shared by all formats           */
+
+// Random opti, then the final tail, returns the map and the index of the first iso algo
+// The first algo of the tail is the output, each other one gives to the previous one
+fn generate_map_head(random_opti: Option<&AlgoConfiguration>, final_tail: &[AlgoConfiguration]
+    ) -> (Vec<AlgoConfiguration>, usize) {
+    let mut res = vec![];
+    if let Some(r) = random_opti {
+        res.push(r.clone());
+    }
+    let output = res.len();
+    if final_tail.is_empty() {
+        res.push(get_final_algoconf());
+    } else {
+        res.extend(final_tail.iter().cloned());
+    }
+    for (ind, algo) in res.iter_mut().enumerate().skip(output + 1) {
+        algo.give.push(ind - 1);
+    }
+    let start_ind = res.len();
+    (res, start_ind)
+}
+
+// The output algo and the random opti give their best cells to every iso
+fn link_map_head(res: &mut [AlgoConfiguration], start_ind: usize, nb_iso: usize,
+    random_opti: bool) {
+    let isos = (start_ind..(start_ind+nb_iso)).collect::<Vec<usize>>();
+    if random_opti {
+        res[0].give.extend(isos.iter());
+        res[1].give.extend(isos.iter());
+    } else {
+        res[0].give.extend(isos.iter());
+    }
+}
+
+fn scale_population(population: &AlgoPopulation, factor: f64) -> AlgoPopulation {
+    match population {
+        AlgoPopulation::WeightofTot(w) => AlgoPopulation::WeightofTot(w * factor),
+        AlgoPopulation::FixedSize(s) => AlgoPopulation::FixedSize(*s),
+    }
+}
+
+// Algo improving the union of the genes improved by the given algos
+fn create_mix_algo(id: String, algos: &[&AlgoConfiguration], method: &str,
+    population: AlgoPopulation) -> Result<AlgoConfiguration, Errcode> {
+    let mut mix = match AlgoConfiguration::method_default(method.to_string()) {
+        Some(m) => m,
+        None => return Err(Errcode::ValidationError("mix method")),
+    };
+    let mut genes = vec![];
+    for algo in algos.iter() {
+        match &algo.impr_genes {
+            Some(g) => genes.extend(g),
+            None => {
+                genes.clear();
+                break;
+            }
+        }
+    }
+    genes.sort_unstable();
+    genes.dedup();
+    mix.id = id;
+    mix.impr_genes = if genes.is_empty() { None } else { Some(genes) };
+    mix.population = population;
+    Ok(mix)
+}
+
+fn map_to_json(map: &[AlgoConfiguration]) -> Result<JsonData, Errcode> {
+    Ok(serde_json::to_string(map)?)
 }
 
 #[pyclass]
 pub struct LabMapAssistant {
-    random_opti: AlgoConfiguration,
+    random_opti: Option<AlgoConfiguration>,
     iso_algos: Vec<AlgoConfiguration>,
     final_tail: Vec<AlgoConfiguration>,
 
@@ -46,36 +152,139 @@ pub struct LabMapAssistant {
 impl LabMapAssistant {
     #[staticmethod]
     pub fn new(format_str: String) -> LabMapAssistant {
-        LabMapAssistant {
-            random_opti: get_random_opti_algoconf(),
-            iso_algos: vec![],
-            final_tail: vec![],
-            mapformat: LabMapFormatType::from(format_str),
-            //          rand  map final
-            priorities: [1.0; 3],
-        }
+        py_err_if_fail!(LabMapAssistant::try_new(format_str), "Lab map assistant creation failed")
     }
 
     pub fn add_opti_part(&mut self, id: String, genes_opt: Vec<usize>, priority: f64,
         method: String, method_cfg: JsonData){
-        self.iso_algos.push(AlgoConfiguration { 
-            id, method,
-            method_options: py_err_if_fail!(
-                GenalgoMethodsConfigurations::from_str(method_cfg),
-                "GenalgoMethodsConfigurations load failed"),
-            give: vec![],
-            impr_genes: Some(genes_opt),
-            population: AlgoPopulation::WeightofTot(priority*self.priorities[1])
-        });
+        let algo = py_err_if_fail!(create_algoconf(id, method, method_cfg,
+            AlgoPopulation::WeightofTot(priority)), "Opti part creation failed");
+        self.__add_opti_part(algo, genes_opt);
+    }
+
+    pub fn add_opti_part_fixed_size(&mut self, id: String, genes_opt: Vec<usize>, size: usize,
+        method: String, method_cfg: JsonData){
+        let algo = py_err_if_fail!(create_algoconf(id, method, method_cfg,
+            AlgoPopulation::FixedSize(size)), "Opti part creation failed");
+        self.__add_opti_part(algo, genes_opt);
+    }
+
+    // Each part added to the final tail gives its best cells to the previous one,
+    // the first one being the output algo
+    pub fn add_final_tail(&mut self, id: String, priority: f64, method: String,
+        method_cfg: JsonData){
+        self.final_tail.push(py_err_if_fail!(create_algoconf(id, method, method_cfg,
+            AlgoPopulation::WeightofTot(priority)), "Final tail creation failed"));
+    }
+
+    pub fn add_final_tail_fixed_size(&mut self, id: String, size: usize, method: String,
+        method_cfg: JsonData){
+        self.final_tail.push(py_err_if_fail!(create_algoconf(id, method, method_cfg,
+            AlgoPopulation::FixedSize(size)), "Final tail creation failed"));
+    }
+
+    pub fn set_priorities(&mut self, random: f64, map: f64, final_tail: f64){
+        self.priorities = [random, map, final_tail];
+    }
+
+    pub fn set_random_opti(&mut self, priority: f64){
+        let mut random_opti = get_random_opti_algoconf();
+        random_opti.population = AlgoPopulation::WeightofTot(priority);
+        self.random_opti = Some(random_opti);
+    }
+
+    pub fn set_random_opti_fixed_size(&mut self, size: usize){
+        let mut random_opti = get_random_opti_algoconf();
+        random_opti.population = AlgoPopulation::FixedSize(size);
+        self.random_opti = Some(random_opti);
+    }
+
+    pub fn disable_random_opti(&mut self){
+        self.random_opti = None;
+    }
+
+    // One opti part per group of genes found by a sensitivity analysis
+    pub fn add_sensitivity_parts(&mut self, analysis: JsonData, method: String,
+        method_cfg: JsonData){
+        let analysis = py_err_if_fail!(SensitivityAnalysis::from_json(&analysis),
+            "Sensitivity analysis load failed");
+        for (n, (genes, priority)) in analysis.groups.iter().zip(analysis.priorities.iter()).enumerate() {
+            self.add_opti_part(format!("ISO_{}", n), genes.clone(), *priority,
+                method.clone(), method_cfg.clone());
+        }
+    }
+
+    pub fn get_output_index(&self) -> usize{
+        if self.random_opti.is_some() { 1 } else { 0 }
     }
 
     pub fn generate_map(&self, mix_method: String) -> JsonData{
         // AlgosID:     <Random opti> <Final tail> <Map>
-        py_err_if_fail!(self.mapformat.generate_map(&self.iso_algos, &self.random_opti,
-            &self.final_tail, &self.priorities, mix_method), "Map generation failed")
+        let random_opti = self.random_opti.as_ref().map(|r| {
+            let mut r = r.clone();
+            r.population = scale_population(&r.population, self.priorities[0]);
+            r
+        });
+        let final_tail = if self.final_tail.is_empty() {
+            vec![get_final_algoconf()]
+        } else {
+            self.final_tail.clone()
+        }.into_iter().map(|mut algo| {
+            algo.population = scale_population(&algo.population, self.priorities[2]);
+            algo
+        }).collect::<Vec<AlgoConfiguration>>();
+        let iso_algos = self.iso_algos.iter().map(|algo| {
+            let mut algo = algo.clone();
+            algo.population = scale_population(&algo.population, self.priorities[1]);
+            algo
+        }).collect::<Vec<AlgoConfiguration>>();
+        py_err_if_fail!(self.mapformat.generate_map(&iso_algos, random_opti.as_ref(),
+            &final_tail, &self.priorities, mix_method), "Map generation failed")
+    }
+}
+
+impl LabMapAssistant {
+    pub fn try_new(format_str: String) -> Result<LabMapAssistant, Errcode> {
+        Ok(LabMapAssistant {
+            random_opti: Some(get_random_opti_algoconf()),
+            iso_algos: vec![],
+            final_tail: vec![],
+            mapformat: LabMapFormatType::from_name(&format_str)?,
+            //          rand  map final
+            priorities: [1.0; 3],
+        })
+    }
+
+    fn __add_opti_part(&mut self, mut algo: AlgoConfiguration, genes_opt: Vec<usize>) {
+        algo.impr_genes = Some(genes_opt);
+        self.iso_algos.push(algo);
     }
 }
 
+// An empty method configuration uses the default configuration of the method
+fn create_algoconf(id: String, method: String, method_cfg: JsonData,
+    population: AlgoPopulation) -> Result<AlgoConfiguration, Errcode> {
+    let method_enum = match GenalgoMethodsAvailable::get_by_name(&method) {
+        Some(m) => m,
+        None => return Err(Errcode::ValidationError("genalgo method")),
+    };
+    let method_options = if method_cfg.is_empty() {
+        GenalgoMethodsConfigurations::default(method_enum)
+    } else {
+        GenalgoMethodsConfigurations::from_str(method_cfg)?
+    };
+    Ok(AlgoConfiguration {
+        id,
+        method,
+        method_options,
+        give: vec![],
+        impr_genes: None,
+        population,
+        diversity: DiversityPreservation::default(),
+        init: InitStrategy::default(),
+    })
+}
+
 pub fn get_random_opti_algoconf() -> AlgoConfiguration {
     AlgoConfiguration {
         id: "Random".to_string(),
@@ -84,5 +293,63 @@ pub fn get_random_opti_algoconf() -> AlgoConfiguration {
         give: vec![],
         impr_genes: Option::None,
         population: AlgoPopulation::WeightofTot(1.0),
+        diversity: DiversityPreservation::default(),
+        init: InitStrategy::default(),
+    }
+}
+
+pub fn get_final_algoconf() -> AlgoConfiguration {
+    let mut algo = AlgoConfiguration::default();
+    algo.id = "Final".to_string();
+    algo
+}
+
+#[test]
+fn test_labmap_formats() {
+    let isos = (0..5).map(|i| {
+        let mut iso = AlgoConfiguration::default();
+        iso.id = format!("ISO{}", i);
+        iso.impr_genes = Some(vec![i]);
+        iso
+    }).collect::<Vec<AlgoConfiguration>>();
+
+    for name in LabMapFormatType::available() {
+        let map: Vec<AlgoConfiguration> = serde_json::from_str(&LabMapFormatType::from_name(name)
+            .unwrap()
+            .generate_map(&isos, Some(&get_random_opti_algoconf()), &[], &[1.0; 3], "Darwin".to_string())
+            .unwrap()).unwrap();
+        assert_eq!(map[1].id, "Final");
+        assert!(map.iter().all(|a| a.give.iter().all(|g| *g < map.len())));
+        assert!(map.iter().any(|a| a.give.contains(&1)));
+        if name == "HierarchicalFormat" {
+            assert_eq!(map.len(), 2 + 5 + 4);
+            assert_eq!(map.last().unwrap().impr_genes, Some(vec![0, 1, 2, 3, 4]));
+            assert_eq!(map.last().unwrap().give, vec![1]);
+        }
     }
+    assert!(matches!(LabMapFormatType::from_name("Nope"), Err(Errcode::LabMapFormatDoesntExist(_, _))));
+}
+
+#[test]
+fn test_labmap_assistant_tail_and_sizes() {
+    assert!(matches!(LabMapAssistant::try_new("Nope".to_string()), Err(Errcode::LabMapFormatDoesntExist(_, _))));
+    let mut assistant = LabMapAssistant::try_new("WheelFormat".to_string()).unwrap();
+    assistant.disable_random_opti();
+    assistant.set_priorities(1.0, 0.5, 2.0);
+    assistant.add_final_tail("Out".to_string(), 1.0, "Darwin".to_string(), "".to_string());
+    assistant.add_final_tail_fixed_size("Refine".to_string(), 50, "Darwin".to_string(), "".to_string());
+    for i in 0..3 {
+        assistant.add_opti_part_fixed_size(format!("ISO{}", i), vec![i], 100 + (i * 10),
+            "Darwin".to_string(), "".to_string());
+    }
+    assert_eq!(assistant.get_output_index(), 0);
+
+    let map: Vec<AlgoConfiguration> = serde_json::from_str(&assistant.generate_map("Darwin".to_string())).unwrap();
+    assert_eq!(map.len(), 2 + 3 + 3);
+    assert_eq!(map[0].id, "Out");
+    assert!(matches!(map[0].population, AlgoPopulation::WeightofTot(w) if w == 2.0));
+    assert_eq!(map[0].give, vec![2, 3, 4]);
+    assert_eq!(map[1].give, vec![0]);
+    assert!(matches!(map[5].population, AlgoPopulation::FixedSize(105)));
+    assert!(map[5..].iter().all(|mix| mix.give.contains(&1)));
 }
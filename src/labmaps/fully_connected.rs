@@ -0,0 +1,22 @@
+use crate::labmaps::*;
+
+// Every iso gives its best cells to all the other ones
+pub struct FullyConnectedFormat;
+
+impl LabMapFormat for FullyConnectedFormat{
+
+    fn generate_map(&self, iso_algos: &[AlgoConfiguration],
+        random_opti: Option<&AlgoConfiguration>, final_tail: &[AlgoConfiguration],
+        _priorities: &[f64; 3], _mix_method: String) -> Result<JsonData, Errcode>{
+
+        let (mut res, start_ind) = generate_map_head(random_opti, final_tail);
+        res.extend(iso_algos.to_vec());
+        let nb_iso = iso_algos.len();
+        for (i, algo) in res.iter_mut().enumerate().skip(start_ind){
+            algo.give.extend((start_ind..(start_ind+nb_iso)).filter(|j| *j != i));
+            algo.give.push(start_ind-1);
+        }
+        link_map_head(&mut res, start_ind, nb_iso, random_opti.is_some());
+        map_to_json(&res)
+    }
+}
@@ -0,0 +1,121 @@
+use crate::algo::{AlgoConfiguration, AlgoID, AlgoPopulation};
+use crate::errors::Errcode;
+use crate::lab::LabConfig;
+use crate::population::plan_populations;
+
+/*  Diagram export of a lab map, one node per algo and one edge per "give" relation.
+ *  When the lab configuration is known, the populations and the number of elites
+ *  migrating along each edge are computed from it. */
+pub struct MapGraph {
+    nodes: Vec<MapGraphNode>,
+    edges: Vec<(AlgoID, AlgoID, String)>,
+}
+
+struct MapGraphNode {
+    lines: Vec<String>,
+    output: bool,
+}
+
+impl MapGraph {
+    pub fn new(
+        map: &[AlgoConfiguration],
+        labconfig: Option<&LabConfig>,
+        output: Option<AlgoID>,
+    ) -> MapGraph {
+        let mut nodes = vec![];
+        let mut edges = vec![];
+        let plan = labconfig.and_then(|cfg| plan_populations(map, cfg.npop, cfg.elite_ratio).ok());
+        for (n, algo) in map.iter().enumerate() {
+            let mut lines = vec![algo.id.clone(), algo.method.clone()];
+            lines.push(match algo.population {
+                AlgoPopulation::WeightofTot(w) => format!("weight {:.3}", w),
+                AlgoPopulation::FixedSize(s) => format!("size {}", s),
+            });
+            let nelites = plan.as_ref().map(|plan| {
+                let (pop, nelites) = plan[n];
+                lines.push(format!("{} cells", pop));
+                nelites
+            });
+            lines.push(match &algo.impr_genes {
+                Some(genes) => format!("genes {:?}", genes),
+                None => "all genes".to_string(),
+            });
+            nodes.push(MapGraphNode {
+                lines,
+                output: output == Some(n),
+            });
+
+            let label = match nelites {
+                Some(nb) => format!("{} elites", nb),
+                None => "elites".to_string(),
+            };
+            for togive in algo.give.iter() {
+                edges.push((n, *togive, label.clone()));
+            }
+        }
+        MapGraph { nodes, edges }
+    }
+
+    pub fn from_json_map(jsdata: &str, labconfig: Option<&LabConfig>) -> Result<MapGraph, Errcode> {
+        let map: Vec<AlgoConfiguration> = serde_json::from_str(jsdata)?;
+        Ok(MapGraph::new(&map, labconfig, None))
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut res = String::from("digraph labmap {\n    node [shape=box];\n");
+        for (n, node) in self.nodes.iter().enumerate() {
+            let label = node
+                .lines
+                .iter()
+                .map(|l| l.replace('"', "\\\""))
+                .collect::<Vec<String>>()
+                .join("\\n");
+            let style = if node.output { ", peripheries=2" } else { "" };
+            res += &format!("    algo{} [label=\"{}\"{}];\n", n, label, style);
+        }
+        for (from, to, label) in self.edges.iter() {
+            res += &format!("    algo{} -> algo{} [label=\"{}\"];\n", from, to, label);
+        }
+        res + "}\n"
+    }
+
+    pub fn to_mermaid(&self) -> String {
+        let mut res = String::from("graph LR\n");
+        for (n, node) in self.nodes.iter().enumerate() {
+            let label = node
+                .lines
+                .iter()
+                .map(|l| l.replace('"', "#quot;"))
+                .collect::<Vec<String>>()
+                .join("<br/>");
+            if node.output {
+                res += &format!("    algo{}[[\"{}\"]]\n", n, label);
+            } else {
+                res += &format!("    algo{}[\"{}\"]\n", n, label);
+            }
+        }
+        for (from, to, label) in self.edges.iter() {
+            res += &format!("    algo{} -->|{}| algo{}\n", from, label, to);
+        }
+        res
+    }
+}
+
+#[test]
+fn test_map_graph_export() {
+    let mut final_algo = AlgoConfiguration::default();
+    final_algo.id = "Final".to_string();
+    let mut iso = AlgoConfiguration::default();
+    iso.id = "ISO_A".to_string();
+    iso.give = vec![0];
+    iso.impr_genes = Some(vec![0, 2]);
+    iso.population = AlgoPopulation::FixedSize(200);
+
+    let graph = MapGraph::new(&[final_algo, iso], Some(&LabConfig::default()), Some(0));
+    let dot = graph.to_dot();
+    assert!(dot.contains("algo0 [label=\"Final\\nDarwin\\nweight 1.000\\n800 cells\\nall genes\", peripheries=2];"));
+    assert!(dot.contains("algo1 -> algo0 [label=\"20 elites\"];"));
+    let mermaid = graph.to_mermaid();
+    assert!(mermaid.contains("algo1[\"ISO_A<br/>Darwin<br/>size 200<br/>200 cells<br/>genes [0, 2]\"]"));
+    assert!(mermaid.contains("algo1 -->|20 elites| algo0"));
+}
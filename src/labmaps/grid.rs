@@ -0,0 +1,57 @@
+use crate::labmaps::*;
+
+// Isos placed on a 2D torus, giving their best cells to their 4 neighbours
+pub struct GridFormat;
+
+impl LabMapFormat for GridFormat{
+
+    fn generate_map(&self, iso_algos: &[AlgoConfiguration],
+        random_opti: Option<&AlgoConfiguration>, final_tail: &[AlgoConfiguration],
+        _priorities: &[f64; 3], _mix_method: String) -> Result<JsonData, Errcode>{
+
+        let (mut res, start_ind) = generate_map_head(random_opti, final_tail);
+        res.extend(iso_algos.to_vec());
+        let nb_iso = iso_algos.len();
+        for i in 0..nb_iso{
+            let algo = &mut res[start_ind+i];
+            algo.give.extend(self.get_neighbours(i, nb_iso).iter().map(|n| start_ind + n));
+            algo.give.push(start_ind-1);
+        }
+        link_map_head(&mut res, start_ind, nb_iso, random_opti.is_some());
+        map_to_json(&res)
+    }
+}
+
+impl GridFormat{
+    // Size of the grid is the smallest square fitting all the isos, the last row
+    // may be shorter and wraps on itself
+    fn get_neighbours(&self, ind: usize, nb: usize) -> Vec<usize> {
+        let cols = (nb as f64).sqrt().ceil() as usize;
+        let rows = nb.div_ceil(cols);
+        let row_len = |r: usize| cols.min(nb - (r * cols));
+        let (row, col) = (ind / cols, ind % cols);
+        let (up, down) = ((row + rows - 1) % rows, (row + 1) % rows);
+
+        let mut res = vec![
+            (row * cols) + ((col + 1) % row_len(row)),
+            (row * cols) + ((col + row_len(row) - 1) % row_len(row)),
+            (up * cols) + (col % row_len(up)),
+            (down * cols) + (col % row_len(down)),
+        ];
+        res.sort_unstable();
+        res.dedup();
+        res.retain(|n| *n != ind);
+        res
+    }
+}
+
+#[test]
+fn test_grid_neighbours() {
+    let grid = GridFormat;
+    assert_eq!(grid.get_neighbours(0, 9), vec![1, 2, 3, 6]);
+    assert_eq!(grid.get_neighbours(4, 9), vec![1, 3, 5, 7]);
+    // Last row only has 1 element
+    assert_eq!(grid.get_neighbours(6, 7), vec![0, 3]);
+    assert_eq!(grid.get_neighbours(0, 2), vec![1]);
+    assert!(grid.get_neighbours(0, 1).is_empty());
+}
@@ -0,0 +1,42 @@
+use crate::labmaps::*;
+
+/*  Layers of algos, each one improving the union of the genes of two algos of the
+ *  previous layer, until a single algo improving every gene gives to the final tail. */
+pub struct HierarchicalFormat;
+
+impl LabMapFormat for HierarchicalFormat{
+
+    fn generate_map(&self, iso_algos: &[AlgoConfiguration],
+        random_opti: Option<&AlgoConfiguration>, final_tail: &[AlgoConfiguration],
+        priorities: &[f64; 3], mix_method: String) -> Result<JsonData, Errcode>{
+
+        let (mut res, start_ind) = generate_map_head(random_opti, final_tail);
+        res.extend(iso_algos.to_vec());
+        let mut layer: Vec<usize> = (start_ind..res.len()).collect();
+        let mut nlayer = 1;
+        while layer.len() > 1 {
+            let mut next_layer = vec![];
+            for (n, pair) in layer.chunks(2).enumerate() {
+                if pair.len() == 1 {
+                    next_layer.push(pair[0]);
+                    continue;
+                }
+                let ind = res.len();
+                let mix = create_mix_algo(format!("Layer{}_{}", nlayer, n),
+                    &[&res[pair[0]], &res[pair[1]]], &mix_method,
+                    AlgoPopulation::WeightofTot(priorities[1]))?;
+                res[pair[0]].give.push(ind);
+                res[pair[1]].give.push(ind);
+                res.push(mix);
+                next_layer.push(ind);
+            }
+            layer = next_layer;
+            nlayer += 1;
+        }
+        if let Some(top) = layer.first() {
+            res[*top].give.push(start_ind-1);
+        }
+        link_map_head(&mut res, start_ind, iso_algos.len(), random_opti.is_some());
+        map_to_json(&res)
+    }
+}
@@ -0,0 +1,236 @@
+use crate::algo::{AlgoConfiguration, AlgoPopulation};
+use crate::diversity::DiversityPreservation;
+use crate::errors::Errcode;
+use crate::genalgomethods::{GenalgoMethodsAvailable, GenalgoMethodsConfigurations};
+use crate::lab::LabConfig;
+use crate::sampling::InitStrategy;
+use crate::utils::JsonData;
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/*  Human-readable version of a lab map, meant to be written by hand in TOML or YAML.
+ *  Algos reference each other by their id instead of their position in the map,
+ *  the method options only have to contain the values overriding the defaults. */
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LabMapFile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lab: Option<LabConfig>,
+    pub algos: Vec<MapFileAlgo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MapFileAlgo {
+    pub id: String,
+    pub method: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub give: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub impr_genes: Option<Vec<usize>>,
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub options: serde_json::Map<String, serde_json::Value>,
+    #[serde(default, skip_serializing_if = "DiversityPreservation::is_default")]
+    pub diversity: DiversityPreservation,
+    #[serde(default, skip_serializing_if = "InitStrategy::is_default")]
+    pub init: InitStrategy,
+}
+
+impl LabMapFile {
+    pub fn from_toml(data: &str) -> Result<LabMapFile, Errcode> {
+        Ok(toml::from_str(data)?)
+    }
+
+    pub fn from_yaml(data: &str) -> Result<LabMapFile, Errcode> {
+        Ok(serde_yaml::from_str(data)?)
+    }
+
+    pub fn to_toml(&self) -> Result<String, Errcode> {
+        Ok(toml::to_string(self)?)
+    }
+
+    pub fn to_yaml(&self) -> Result<String, Errcode> {
+        Ok(serde_yaml::to_string(self)?)
+    }
+
+    // Format chosen from the file extension: .toml, .yaml / .yml, or .json for a raw map
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<LabMapFile, Errcode> {
+        let data = std::fs::read_to_string(&path)?;
+        match path.as_ref().extension().and_then(|e| e.to_str()) {
+            Some("toml") => LabMapFile::from_toml(&data),
+            Some("yaml") | Some("yml") => LabMapFile::from_yaml(&data),
+            Some("json") => {
+                LabMapFile::from_map(&serde_json::from_str::<Vec<AlgoConfiguration>>(&data)?, None, None)
+            }
+            _ => Err(Errcode::ValidationError("map file extension")),
+        }
+    }
+
+    pub fn from_map(
+        map: &[AlgoConfiguration],
+        lab: Option<LabConfig>,
+        output: Option<usize>,
+    ) -> Result<LabMapFile, Errcode> {
+        let mut algos = vec![];
+        for cfg in map.iter() {
+            let (weight, size) = match cfg.population {
+                AlgoPopulation::WeightofTot(w) => (Some(w), None),
+                AlgoPopulation::FixedSize(s) => (None, Some(s)),
+            };
+            let mut give = vec![];
+            for id in cfg.give.iter() {
+                match map.get(*id) {
+                    Some(c) => give.push(c.id.clone()),
+                    None => return Err(Errcode::IdDoesntExist(*id)),
+                }
+            }
+            algos.push(MapFileAlgo {
+                id: cfg.id.clone(),
+                method: cfg.method.clone(),
+                weight,
+                size,
+                give,
+                impr_genes: cfg.impr_genes.clone(),
+                options: cfg.method_options.get_options()?,
+                diversity: cfg.diversity.clone(),
+                init: cfg.init,
+            });
+        }
+        let output = match output {
+            Some(id) => match map.get(id) {
+                Some(c) => Some(c.id.clone()),
+                None => return Err(Errcode::IdDoesntExist(id)),
+            },
+            None => None,
+        };
+        let mapfile = LabMapFile { output, lab, algos };
+        mapfile.get_indexes()?;
+        Ok(mapfile)
+    }
+
+    pub fn to_map(&self) -> Result<Vec<AlgoConfiguration>, Errcode> {
+        let indexes = self.get_indexes()?;
+        let mut map = vec![];
+        for algo in self.algos.iter() {
+            let method = match GenalgoMethodsAvailable::get_by_name(&algo.method) {
+                Some(m) => m,
+                None => return Err(Errcode::ValidationError("genalgo method")),
+            };
+            let population = match (algo.weight, algo.size) {
+                (Some(w), None) => AlgoPopulation::WeightofTot(w),
+                (None, Some(s)) => AlgoPopulation::FixedSize(s),
+                (None, None) => AlgoPopulation::WeightofTot(1.0),
+                (Some(_), Some(_)) => {
+                    return Err(Errcode::ValidationError("algo population, both weight and size set"))
+                }
+            };
+            let mut give = vec![];
+            for id in algo.give.iter() {
+                match indexes.get(id.as_str()) {
+                    Some(ind) => give.push(*ind),
+                    None => return Err(Errcode::AlgoIdDoesntExist(id.clone())),
+                }
+            }
+            map.push(AlgoConfiguration {
+                id: algo.id.clone(),
+                method: algo.method.clone(),
+                method_options: GenalgoMethodsConfigurations::from_options(method, &algo.options)?,
+                give,
+                impr_genes: algo.impr_genes.clone(),
+                population,
+                diversity: algo.diversity.clone(),
+                init: algo.init,
+            });
+        }
+        Ok(map)
+    }
+
+    pub fn to_json_map(&self) -> Result<JsonData, Errcode> {
+        Ok(serde_json::to_string(&self.to_map()?)?)
+    }
+
+    pub fn get_output_index(&self) -> Result<Option<usize>, Errcode> {
+        match &self.output {
+            Some(out) => match self.get_indexes()?.get(out.as_str()) {
+                Some(ind) => Ok(Some(*ind)),
+                None => Err(Errcode::AlgoIdDoesntExist(out.clone())),
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn get_indexes(&self) -> Result<HashMap<&str, usize>, Errcode> {
+        let mut indexes = HashMap::new();
+        for (n, algo) in self.algos.iter().enumerate() {
+            if indexes.insert(algo.id.as_str(), n).is_some() {
+                return Err(Errcode::DuplicateAlgoId(algo.id.clone()));
+            }
+        }
+        Ok(indexes)
+    }
+}
+
+#[test]
+fn test_mapfile_toml_to_map() {
+    let mapfile = LabMapFile::from_toml(
+        r#"
+        output = "Final"
+
+        [[algos]]
+        id = "Final"
+        method = "Darwin"
+        options = { gene_reroll_proba = 0.2 }
+
+        [[algos]]
+        id = "ISO_A"
+        method = "Darwin"
+        size = 200
+        impr_genes = [0, 1]
+        give = ["Final", "Random"]
+
+        [[algos]]
+        id = "Random"
+        method = "RandomOpti"
+        weight = 0.5
+        give = ["ISO_A"]
+        init = "Halton"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(mapfile.get_output_index().unwrap(), Some(0));
+    let map = mapfile.to_map().unwrap();
+    assert_eq!(map[1].give, vec![0, 2]);
+    assert_eq!(map[2].give, vec![1]);
+    assert_eq!(map[2].init, InitStrategy::Halton);
+    assert_eq!(
+        map[0].method_options.get_options().unwrap()["gene_reroll_proba"],
+        0.2
+    );
+
+    let mapfile = LabMapFile::from_map(&map, Some(LabConfig::default()), Some(0)).unwrap();
+    let back = LabMapFile::from_yaml(&mapfile.to_yaml().unwrap()).unwrap();
+    assert_eq!(back.algos[1].give, vec!["Final".to_string(), "Random".to_string()]);
+    assert_eq!(back.algos[1].size, Some(200));
+    let back = LabMapFile::from_toml(&mapfile.to_toml().unwrap()).unwrap();
+    assert_eq!(back.to_json_map().unwrap(), mapfile.to_json_map().unwrap());
+}
+
+#[test]
+fn test_mapfile_errors() {
+    let unknown_ref = "algos:\n  - id: A\n    method: Darwin\n    give: [B]\n";
+    assert!(matches!(
+        LabMapFile::from_yaml(unknown_ref).unwrap().to_map(),
+        Err(Errcode::AlgoIdDoesntExist(_))
+    ));
+    let unknown_opt = "algos:\n  - id: A\n    method: Darwin\n    options: {mutation: 0.5}\n";
+    assert!(matches!(
+        LabMapFile::from_yaml(unknown_opt).unwrap().to_map(),
+        Err(Errcode::UnknownMethodOption(_, _))
+    ));
+}
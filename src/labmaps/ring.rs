@@ -0,0 +1,26 @@
+use crate::labmaps::*;
+
+// Each iso gives its best cells to the next one, the last closing the ring
+pub struct RingFormat;
+
+impl LabMapFormat for RingFormat{
+
+    fn generate_map(&self, iso_algos: &[AlgoConfiguration],
+        random_opti: Option<&AlgoConfiguration>, final_tail: &[AlgoConfiguration],
+        _priorities: &[f64; 3], _mix_method: String) -> Result<JsonData, Errcode>{
+
+        let (mut res, start_ind) = generate_map_head(random_opti, final_tail);
+        res.extend(iso_algos.to_vec());
+        let nb_iso = iso_algos.len();
+        for i in 0..nb_iso{
+            let next = start_ind + ((i + 1) % nb_iso);
+            let algo = &mut res[start_ind+i];
+            if next != start_ind + i {
+                algo.give.push(next);
+            }
+            algo.give.push(start_ind-1);
+        }
+        link_map_head(&mut res, start_ind, nb_iso, random_opti.is_some());
+        map_to_json(&res)
+    }
+}
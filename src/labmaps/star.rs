@@ -0,0 +1,27 @@
+use crate::labmaps::*;
+
+// Every iso exchanges its best cells with a hub improving all their genes
+pub struct StarFormat;
+
+impl LabMapFormat for StarFormat{
+
+    fn generate_map(&self, iso_algos: &[AlgoConfiguration],
+        random_opti: Option<&AlgoConfiguration>, final_tail: &[AlgoConfiguration],
+        priorities: &[f64; 3], mix_method: String) -> Result<JsonData, Errcode>{
+
+        let (mut res, start_ind) = generate_map_head(random_opti, final_tail);
+        res.extend(iso_algos.to_vec());
+        let nb_iso = iso_algos.len();
+        let hub_ind = res.len();
+        let mut hub = create_mix_algo("Hub".to_string(), &iso_algos.iter().collect::<Vec<_>>(),
+            &mix_method, AlgoPopulation::WeightofTot(priorities[1]))?;
+        hub.give = (start_ind..hub_ind).collect();
+        hub.give.push(start_ind-1);
+        for algo in res[start_ind..].iter_mut(){
+            algo.give.push(hub_ind);
+        }
+        res.push(hub);
+        link_map_head(&mut res, start_ind, nb_iso, random_opti.is_some());
+        map_to_json(&res)
+    }
+}
@@ -4,49 +4,30 @@ pub struct WheelFormat;
 
 impl LabMapFormat for WheelFormat{
 
-    fn generate_map(&self, iso_algos: &Vec<AlgoConfiguration>,
-        random_opti: &AlgoConfiguration, final_tail: &Vec<AlgoConfiguration>,
+    fn generate_map(&self, iso_algos: &[AlgoConfiguration],
+        random_opti: Option<&AlgoConfiguration>, final_tail: &[AlgoConfiguration],
         priorities: &[f64; 3], mix_method: String) -> Result<JsonData, Errcode>{
 
-        let mut res = vec![];
-        res.push(random_opti.clone());
-        let start_ind =  if final_tail.len() == 0{
-            res.push(self.generate_final_algo());
-            2
-        }else{
-            res.extend(final_tail.clone());
-            1 + final_tail.len()
-        };
-
-        res.extend(iso_algos.clone());
+        let (mut res, start_ind) = generate_map_head(random_opti, final_tail);
+        res.extend(iso_algos.to_vec());
         if iso_algos.len() <= 2{    // No Mix layer
             for algo in res[start_ind..].iter_mut(){
                 algo.give.push(start_ind-1);
             }
         } else {                    // Create Mix layers
-            self.generate_mixes(&mut res, priorities[1], start_ind, iso_algos.len(), mix_method, 1);
+            self.generate_mixes(&mut res, priorities[1], start_ind, iso_algos.len(), mix_method, start_ind-1)?;
         }
 
-        // Give the new best cell to every ISO
-        res[1].id = "Final".to_string();
-        res[1].give = (start_ind..(start_ind+iso_algos.len())).collect();
-        res[1].population = AlgoPopulation::WeightofTot(priorities[2]);
-        // Give RandomOpti to every ISO
-        res[0].id = "Random".to_string();
-        res[0].give = (start_ind..(start_ind+iso_algos.len())).collect();
-        res[0].population = AlgoPopulation::WeightofTot(priorities[0]);
-        self.to_json(res)
+        // Give the new best cell to every ISO, and RandomOpti to every ISO
+        link_map_head(&mut res, start_ind, iso_algos.len(), random_opti.is_some());
+        map_to_json(&res)
     }
 }
 impl WheelFormat{
     pub fn new() -> WheelFormat{ WheelFormat {} }
 
-    fn generate_final_algo(&self) -> AlgoConfiguration{
-        AlgoConfiguration::default()
-    }
-
     fn generate_mixes(&self, algos: &mut Vec<AlgoConfiguration>, priority: f64,
-        start: usize, nb_iso: usize, method: String, output: usize) {
+        start: usize, nb_iso: usize, method: String, output: usize) -> Result<(), Errcode> {
 
         for i in 0..nb_iso{
             let j = if i == nb_iso-1 { 0 } else { i + 1 };
@@ -55,49 +36,37 @@ impl WheelFormat{
             algos[start+j].give.push(ind);
 
             let mut algo_mix = self.create_mix(&algos[start+i], &algos[start+j],
-                priority, method.clone(), output);
+                priority, &method, output)?;
             if i > 0{
                 algo_mix.give.push(algos.len() - 1);
             }
-            algo_mix.population = AlgoPopulation::WeightofTot(priority + 
-                (if let AlgoPopulation::WeightofTot(p) = algos[start+i].population { p }
-                else { unreachable!() } *
-                if let AlgoPopulation::WeightofTot(p) = algos[start+j].population { p }
-                else { unreachable!() }));
+            algo_mix.population = self.mix_population(priority,
+                &algos[start+i].population, &algos[start+j].population);
             algos.push(algo_mix);
         }
         let nmix = algos.len() - 1;
         algos[start+nb_iso].give.push(nmix);
+        Ok(())
     }
 
-    fn create_mix(&self, iso_a: &AlgoConfiguration, iso_b: &AlgoConfiguration,
-        priority: f64, method: String, output: usize) -> AlgoConfiguration {
-        let mut algo = AlgoConfiguration::method_default(method).unwrap();
-
-        algo.id = format!("Mix_{}_{}", iso_a.id, iso_b.id);
-        algo.impr_genes = Some(self.extract_impr_genes(iso_a, iso_b));
-        algo.give = vec![output];
-        algo.population = AlgoPopulation::WeightofTot(priority);
-        algo
-    }
-
-    fn extract_impr_genes(&self, iso_a: &AlgoConfiguration, iso_b: &AlgoConfiguration
-        ) -> Vec<usize>{
-
-        let mut res = vec![];
-        if let Some(g) = &iso_a.impr_genes{
-            res.extend(g);
-        }
-        if let Some(g) = &iso_b.impr_genes{
-            res.extend(g);
+    // A mix of two fixed size isos gets their average size,
+    // a fixed size iso mixed with a weighted one falls back to WeightofTot(priority)
+    fn mix_population(&self, priority: f64, pop_a: &AlgoPopulation, pop_b: &AlgoPopulation
+        ) -> AlgoPopulation {
+        match (pop_a, pop_b) {
+            (AlgoPopulation::WeightofTot(a), AlgoPopulation::WeightofTot(b)) =>
+                AlgoPopulation::WeightofTot(priority + (a * b)),
+            (AlgoPopulation::FixedSize(a), AlgoPopulation::FixedSize(b)) =>
+                AlgoPopulation::FixedSize((a + b) / 2),
+            _ => AlgoPopulation::WeightofTot(priority),
         }
-        res.sort();
-        res.dedup();
-        res
     }
 
-    fn to_json(&self, data: Vec<AlgoConfiguration>) -> Result<JsonData, Errcode> {
-        Ok(serde_json::to_string(&data)?)
+    fn create_mix(&self, iso_a: &AlgoConfiguration, iso_b: &AlgoConfiguration,
+        priority: f64, method: &str, output: usize) -> Result<AlgoConfiguration, Errcode> {
+        let mut algo = create_mix_algo(format!("Mix_{}_{}", iso_a.id, iso_b.id),
+            &[iso_a, iso_b], method, AlgoPopulation::WeightofTot(priority))?;
+        algo.give = vec![output];
+        Ok(algo)
     }
 }
-
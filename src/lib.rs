@@ -1,13 +1,31 @@
 #![deny(unsafe_code)]
 
+// Lets the derive macros refer to ::petridish inside this crate
+extern crate self as petridish;
+
 pub mod algo;
+pub mod cache;
 pub mod cell;
 pub mod dataset;
+pub mod discrete;
+pub mod diversity;
 pub mod errors;
 pub mod genalgo;
 pub mod genalgomethods;
+pub mod genome;
+pub mod gp;
+pub mod halloffame;
+pub mod history;
 pub mod lab;
 pub mod labmaps;
+pub mod migration;
+pub mod neuro;
+pub mod permutation;
+pub mod population;
+pub mod restart;
+pub mod sampling;
+pub mod seeding;
+pub mod sensitivity;
 pub mod utils;
 
 #[macro_use]
@@ -0,0 +1,120 @@
+use crate::cell::{genome_length_is_valid, Cell, CellData, Genome};
+use crate::errors::Errcode;
+use crate::utils::JsonData;
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/*  Change of the genome layout between a version of a cell and the following one
+ *      AddGenes: default values inserted at the given position
+ *      DropGenes: genes removed, indexes in the old genome
+ *      ReorderGenes: gene n of the new genome is the gene order[n] of the old one */
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum GenomeAdapter {
+    AddGenes { at: usize, values: Vec<f64> },
+    DropGenes { genes: Vec<usize> },
+    ReorderGenes { order: Vec<usize> },
+}
+
+impl GenomeAdapter {
+    pub fn from_json(jsdata: JsonData) -> Result<GenomeAdapter, Errcode> {
+        Ok(serde_json::from_str(&jsdata)?)
+    }
+
+    pub fn apply(&self, genome: &Genome) -> Result<Genome, Errcode> {
+        match self {
+            GenomeAdapter::AddGenes { at, values } => {
+                if *at > genome.len() {
+                    return Err(Errcode::IdDoesntExist(*at));
+                }
+                let mut res = genome.clone();
+                res.splice(*at..*at, values.iter().cloned());
+                Ok(res)
+            }
+            GenomeAdapter::DropGenes { genes } => {
+                if let Some(g) = genes.iter().find(|g| **g >= genome.len()) {
+                    return Err(Errcode::IdDoesntExist(*g));
+                }
+                Ok(genome.iter().enumerate().filter(|(n, _)| !genes.contains(n)).map(|(_, g)| *g).collect())
+            }
+            GenomeAdapter::ReorderGenes { order } => {
+                let mut res = vec![];
+                for n in order.iter() {
+                    match genome.get(*n) {
+                        Some(g) => res.push(*g),
+                        None => return Err(Errcode::IdDoesntExist(*n)),
+                    }
+                }
+                Ok(res)
+            }
+        }
+    }
+}
+
+/*  Adapters registered for each version, applied in chain to bring a genome to the
+ *  current version of the cell. Versions without registered adapters go through
+ *  Cell::genome_version_adapt. */
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct GenomeMigrations {
+    adapters: BTreeMap<u64, Vec<GenomeAdapter>>,
+}
+
+impl GenomeMigrations {
+    pub fn register(&mut self, from_version: u64, adapter: GenomeAdapter) {
+        self.adapters.entry(from_version).or_default().push(adapter);
+    }
+
+    pub fn migrate<T: Cell>(&self, genome: &Genome, version: u64) -> Result<Genome, Errcode> {
+        let current = T::genome_version();
+        if version > current {
+            return Err(Errcode::GenomeVersionTooRecent(version, current));
+        }
+        let mut res = genome.clone();
+        for v in version..current {
+            res = match self.adapters.get(&v) {
+                Some(adapters) => {
+                    for adapter in adapters.iter() {
+                        res = adapter.apply(&res)?;
+                    }
+                    res
+                }
+                None => T::genome_version_adapt(&res, v),
+            };
+        }
+        if !genome_length_is_valid::<T>(res.len()) {
+            return Err(Errcode::SizeError("migrated genome", T::get_genome_length(), res.len()));
+        }
+        Ok(res)
+    }
+
+    pub fn migrate_cell<T: Cell>(&self, cell: &CellData) -> Result<CellData, Errcode> {
+        Ok(CellData {
+            genome: self.migrate::<T>(&cell.genome, cell.version)?,
+            score: cell.score,
+            version: T::genome_version(),
+        })
+    }
+}
+
+#[test]
+fn test_genome_migration() {
+    // The two genes of version 0 were swapped in version 1
+    crate::cell::test_cell!(TestCell, (), 3, adapt: |genome: &Genome, _| vec![genome[1], genome[0]], {
+        fn genome_version() -> u64 { 2 }
+    });
+
+    let mut migrations = GenomeMigrations::default();
+    migrations.register(1, GenomeAdapter::AddGenes { at: 1, values: vec![0.5] });
+    assert_eq!(migrations.migrate::<TestCell>(&vec![0.1, 0.2], 0).unwrap(), vec![0.2, 0.5, 0.1]);
+    let cell = CellData { genome: vec![0.1, 0.2], score: 1.0, version: 1 };
+    assert_eq!(migrations.migrate_cell::<TestCell>(&cell).unwrap().version, 2);
+    assert!(matches!(migrations.migrate::<TestCell>(&vec![0.1], 1), Err(Errcode::SizeError(_, 3, 2))));
+    assert!(matches!(migrations.migrate::<TestCell>(&vec![0.1, 0.2, 0.3], 3),
+        Err(Errcode::GenomeVersionTooRecent(3, 2))));
+
+    let drop = GenomeAdapter::DropGenes { genes: vec![0, 2] };
+    assert_eq!(drop.apply(&vec![0.1, 0.2, 0.3]).unwrap(), vec![0.2]);
+    let reorder = GenomeAdapter::from_json("{\"ReorderGenes\": {\"order\": [2, 0]}}".to_string()).unwrap();
+    assert_eq!(reorder.apply(&vec![0.1, 0.2, 0.3]).unwrap(), vec![0.3, 0.1]);
+    assert!(matches!(reorder.apply(&vec![0.1]), Err(Errcode::IdDoesntExist(2))));
+}
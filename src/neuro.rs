@@ -0,0 +1,374 @@
+use crate::algo::Algo;
+use crate::cell::{Cell, CellData, Genome};
+use crate::dataset::GenalgoData;
+use crate::errors::Errcode;
+use crate::lab::Lab;
+use crate::utils::JsonData;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::marker::PhantomData;
+
+#[derive(Copy, Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub enum Activation {
+    Identity,
+    Relu,
+    LeakyRelu,
+    Tanh,
+    Sigmoid,
+}
+
+impl Activation {
+    pub fn apply(&self, x: f64) -> f64 {
+        match self {
+            Activation::Identity => x,
+            Activation::Relu => x.max(0.0),
+            Activation::LeakyRelu => if x > 0.0 { x } else { 0.01 * x },
+            Activation::Tanh => x.tanh(),
+            Activation::Sigmoid => 1.0 / (1.0 + (-x).exp()),
+        }
+    }
+}
+
+/*  Loss of the outputs of the network against the targets of a row:
+ *      Mse, Mae: mean squared / absolute error over the outputs
+ *      BinaryCrossEntropy: outputs are probabilities (Sigmoid), targets 0 or 1
+ *      CrossEntropy: softmax of the outputs against one-hot targets */
+#[derive(Copy, Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub enum Loss {
+    Mse,
+    Mae,
+    BinaryCrossEntropy,
+    CrossEntropy,
+}
+
+impl Loss {
+    pub fn compute(&self, outputs: &[f64], targets: &[f64]) -> f64 {
+        let n = outputs.len() as f64;
+        let pairs = outputs.iter().zip(targets.iter());
+        match self {
+            Loss::Mse => pairs.map(|(o, t)| (o - t).powi(2)).sum::<f64>() / n,
+            Loss::Mae => pairs.map(|(o, t)| (o - t).abs()).sum::<f64>() / n,
+            Loss::BinaryCrossEntropy => pairs.map(|(o, t)| {
+                let p = o.clamp(1e-12, 1.0 - 1e-12);
+                -(t * p.ln() + (1.0 - t) * (1.0 - p).ln())
+            }).sum::<f64>() / n,
+            Loss::CrossEntropy => {
+                let max = outputs.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+                let logsum = outputs.iter().map(|o| (o - max).exp()).sum::<f64>().ln() + max;
+                pairs.map(|(o, t)| -t * (o - logsum)).sum::<f64>()
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct LayerSpec {
+    pub size: usize,
+    pub activation: Activation,
+}
+
+// Fully connected feed-forward network, the last layer being the outputs
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct Topology {
+    pub inputs: usize,
+    pub layers: Vec<LayerSpec>,
+}
+
+impl Topology {
+    pub fn outputs(&self) -> usize {
+        self.layers.last().map(|l| l.size).unwrap_or(self.inputs)
+    }
+
+    // Weights and biases of all the layers
+    pub fn nparams(&self) -> usize {
+        let mut prev = self.inputs;
+        let mut res = 0;
+        for layer in self.layers.iter() {
+            res += (prev + 1) * layer.size;
+            prev = layer.size;
+        }
+        res
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct DenseLayer {
+    pub weights: Vec<Vec<f64>>, // One row of input weights per neuron
+    pub biases: Vec<f64>,
+    pub activation: Activation,
+}
+
+impl DenseLayer {
+    pub fn forward(&self, inputs: &[f64]) -> Vec<f64> {
+        self.weights.iter().zip(self.biases.iter())
+            .map(|(w, b)| w.iter().zip(inputs.iter()).map(|(w, x)| w * x).sum::<f64>() + b)
+            .map(|x| self.activation.apply(x))
+            .collect()
+    }
+}
+
+/*  Network of a genome, genes in [0, 1] being mapped to parameters in [-scale, scale].
+ *  For each layer, the genome holds the weights neuron by neuron, then the biases. */
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct Mlp {
+    pub layers: Vec<DenseLayer>,
+}
+
+impl Mlp {
+    pub fn from_genome(topology: &Topology, genome: &Genome, scale: f64) -> Result<Mlp, Errcode> {
+        if genome.len() != topology.nparams() {
+            return Err(Errcode::SizeError("network genome", topology.nparams(), genome.len()));
+        }
+        let mut params = genome.iter().map(|g| (2.0 * g - 1.0) * scale);
+        let mut prev = topology.inputs;
+        let mut layers = vec![];
+        for spec in topology.layers.iter() {
+            let weights = (0..spec.size).map(|_| params.by_ref().take(prev).collect()).collect();
+            let biases = params.by_ref().take(spec.size).collect();
+            layers.push(DenseLayer { weights, biases, activation: spec.activation });
+            prev = spec.size;
+        }
+        Ok(Mlp { layers })
+    }
+
+    // Same layer shapes and activations as the topology
+    pub fn check_topology(&self, topology: &Topology) -> Result<(), Errcode> {
+        if self.layers.len() != topology.layers.len() {
+            return Err(Errcode::SizeError("network layers", topology.layers.len(), self.layers.len()));
+        }
+        let mut prev = topology.inputs;
+        for (layer, spec) in self.layers.iter().zip(topology.layers.iter()) {
+            if layer.weights.len() != spec.size {
+                return Err(Errcode::SizeError("network layer neurons", spec.size, layer.weights.len()));
+            }
+            if layer.biases.len() != spec.size {
+                return Err(Errcode::SizeError("network layer biases", spec.size, layer.biases.len()));
+            }
+            if let Some(w) = layer.weights.iter().find(|w| w.len() != prev) {
+                return Err(Errcode::SizeError("network neuron weights", prev, w.len()));
+            }
+            if layer.activation != spec.activation {
+                return Err(Errcode::ValidationError("network layer activation differs from the topology"));
+            }
+            prev = spec.size;
+        }
+        Ok(())
+    }
+
+    pub fn to_genome(&self, scale: f64) -> Genome {
+        let mut genome = vec![];
+        for layer in self.layers.iter() {
+            for p in layer.weights.iter().flatten().chain(layer.biases.iter()) {
+                genome.push(if scale > 0.0 { ((p / scale + 1.0) / 2.0).clamp(0.0, 1.0) } else { 0.5 });
+            }
+        }
+        genome
+    }
+
+    pub fn forward(&self, inputs: &[f64]) -> Vec<f64> {
+        let mut values = inputs.to_vec();
+        for layer in self.layers.iter() {
+            values = layer.forward(&values);
+        }
+        values
+    }
+}
+
+/*  Neuroevolution problem: topology of the network, loss, and scale of its parameters.
+ *  The rows of the datasets are the inputs of the network followed by the targets. */
+pub trait MlpSpec: 'static {
+    fn topology() -> Topology;
+
+    fn loss() -> Loss {
+        Loss::Mse
+    }
+
+    fn weight_scale() -> f64 {
+        2.0
+    }
+}
+
+/*  Cell scored with the mean loss of its network over the rows it evaluated, to minimize.
+ *  Rows shorter than the inputs and targets are ignored. Until a row is evaluated, or if its
+ *  genome doesn't fit the topology and it has no network, the cell gets the worst score. */
+pub struct MlpCell<S: MlpSpec> {
+    celldata: CellData,
+    network: Option<Mlp>,
+    ninputs: usize,
+    noutputs: usize,
+    loss: f64,
+    nrows: usize,
+    _spec: PhantomData<S>,
+}
+
+impl<S: MlpSpec> MlpCell<S> {
+    pub fn new(genome: &Genome) -> MlpCell<S> {
+        let topology = S::topology();
+        MlpCell {
+            celldata: CellData { genome: genome.clone(), score: f64::MAX, version: Self::genome_version() },
+            network: Mlp::from_genome(&topology, genome, S::weight_scale()).ok(),
+            ninputs: topology.inputs,
+            noutputs: topology.outputs(),
+            loss: 0.0,
+            nrows: 0,
+            _spec: PhantomData,
+        }
+    }
+
+    pub fn get_network(&self) -> Option<&Mlp> {
+        self.network.as_ref()
+    }
+}
+
+impl<S: MlpSpec> Cell for MlpCell<S> {
+    type Data = GenalgoData;
+
+    fn get_genome_length() -> usize {
+        S::topology().nparams()
+    }
+
+    fn get_data(&self) -> &CellData {
+        &self.celldata
+    }
+
+    fn action(&mut self, data: &GenalgoData) {
+        let network = match &self.network {
+            Some(n) if data.len() >= self.ninputs + self.noutputs => n,
+            _ => return,
+        };
+        let (inputs, targets) = data.split_at(self.ninputs);
+        self.loss += S::loss().compute(&network.forward(inputs), targets);
+        self.nrows += 1;
+        let score = self.loss / (self.nrows as f64);
+        self.celldata.score = if score.is_finite() { score } else { f64::MAX };
+    }
+
+    fn reset(&mut self, genome: &Genome) {
+        self.celldata.genome = genome.clone();
+        self.celldata.score = f64::MAX;
+        let topology = S::topology();
+        self.network = Mlp::from_genome(&topology, genome, S::weight_scale()).ok();
+        self.ninputs = topology.inputs;
+        self.noutputs = topology.outputs();
+        self.loss = 0.0;
+        self.nrows = 0;
+    }
+
+    fn genome_version_adapt(genome: &Genome, _version: u64) -> Genome {
+        genome.clone()
+    }
+}
+
+pub struct MlpAlgo<S: MlpSpec> {
+    _spec: PhantomData<S>,
+}
+
+impl<S: MlpSpec> Algo for MlpAlgo<S> {
+    type CellType = MlpCell<S>;
+
+    fn new() -> Self where Self: Sized {
+        MlpAlgo { _spec: PhantomData }
+    }
+
+    // Genomes as the layers of the network
+    fn genome_from_json(&self, jsdata: JsonData) -> Result<Genome, Errcode> {
+        let network: Mlp = serde_json::from_str(&jsdata)?;
+        network.check_topology(&S::topology())?;
+        Ok(network.to_genome(S::weight_scale()))
+    }
+
+    fn genome_to_json(&self, genome: Genome) -> Result<JsonData, Errcode> {
+        Ok(serde_json::to_string(&Mlp::from_genome(&S::topology(), &genome, S::weight_scale())?)?)
+    }
+
+    fn initialize_cells(&mut self, _pop: &mut Vec<Self::CellType>) {}
+
+    fn create_cell_from_genome(&self, genome: &Genome) -> Self::CellType {
+        MlpCell::new(genome)
+    }
+
+    fn recv_special_data(&mut self, _data: &serde_json::Value) -> Result<(), JsonData> {
+        Ok(())
+    }
+
+    // "predict" method: outputs of the network of a genome for the given inputs
+    fn send_special_data(&self, params: &serde_json::Value) -> Result<JsonData, JsonData> {
+        if params.get("method").and_then(|m| m.as_str()) != Some("predict") {
+            return Err("Please specify method: predict".to_string());
+        }
+        let genome: Genome = serde_json::from_value(params["genome"].clone()).map_err(|e| e.to_string())?;
+        let inputs: Vec<f64> = serde_json::from_value(params["inputs"].clone()).map_err(|e| e.to_string())?;
+        let network = Mlp::from_genome(&S::topology(), &genome, S::weight_scale()).map_err(|e| e.to_string())?;
+        Ok(json!({"result": network.forward(&inputs)}).to_string())
+    }
+
+    fn process_data(&mut self, pop: &mut Vec<Self::CellType>, data: &GenalgoData) {
+        for cell in pop.iter_mut() {
+            cell.action(data);
+        }
+    }
+
+    fn check_generation_over(&self, _genalgo: &Lab<Self::CellType>) -> bool {
+        true
+    }
+
+    fn reset(&mut self) {}
+}
+
+#[test]
+fn test_mlp_network() {
+    let topology = Topology {
+        inputs: 2,
+        layers: vec![
+            LayerSpec { size: 2, activation: Activation::Relu },
+            LayerSpec { size: 1, activation: Activation::Identity },
+        ],
+    };
+    assert_eq!((topology.nparams(), topology.outputs()), (9, 1));
+
+    // Hidden neurons x0 - x1 and x1 - x0, output their sum: |x0 - x1|
+    let params = [1.0, -1.0, -1.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0];
+    let genome: Genome = params.iter().map(|p| (p / 2.0 + 1.0) / 2.0).collect();
+    let network = Mlp::from_genome(&topology, &genome, 2.0).unwrap();
+    assert_eq!(network.layers[0].weights, vec![vec![1.0, -1.0], vec![-1.0, 1.0]]);
+    assert_eq!(network.forward(&[0.25, 1.0]), vec![0.75]);
+    assert_eq!(network.to_genome(2.0), genome);
+    assert!(matches!(Mlp::from_genome(&topology, &vec![0.5; 4], 2.0), Err(Errcode::SizeError(_, 9, 4))));
+
+    // Other activations or layer shapes, with the same number of parameters
+    assert!(network.check_topology(&topology).is_ok());
+    let mut other = topology.clone();
+    other.layers[1].activation = Activation::Sigmoid;
+    assert!(matches!(network.check_topology(&other), Err(Errcode::ValidationError(_))));
+    let other = Topology { inputs: 6, layers: vec![LayerSpec { size: 1, activation: Activation::Relu },
+        LayerSpec { size: 1, activation: Activation::Identity }] };
+    assert_eq!(other.nparams(), 9);
+    assert!(matches!(network.check_topology(&other), Err(Errcode::SizeError(_, 1, 2))));
+    let mut other = topology.clone();
+    other.inputs = 3;
+    assert!(matches!(network.check_topology(&other), Err(Errcode::SizeError(_, 3, 2))));
+
+    // Worst score until a row is evaluated, cells without network are never scored
+    struct AbsDiff;
+    impl MlpSpec for AbsDiff {
+        fn topology() -> Topology {
+            Topology { inputs: 2, layers: vec![LayerSpec { size: 2, activation: Activation::Relu },
+                LayerSpec { size: 1, activation: Activation::Identity }] }
+        }
+    }
+    let mut cell: MlpCell<AbsDiff> = MlpCell::new(&genome);
+    assert_eq!(cell.get_data().score, f64::MAX);
+    cell.action(&vec![0.25]);
+    assert_eq!(cell.get_data().score, f64::MAX);
+    cell.action(&vec![0.25, 1.0, 0.75]);
+    assert_eq!(cell.get_data().score, 0.0);
+    cell.reset(&vec![0.5; 4]);
+    cell.action(&vec![0.25, 1.0, 0.75]);
+    assert!(cell.get_network().is_none() && (cell.get_data().score == f64::MAX));
+
+    assert_eq!(Loss::Mse.compute(&[1.0, 2.0], &[0.0, 0.0]), 2.5);
+    assert_eq!(Loss::Mae.compute(&[1.0, -2.0], &[0.0, 0.0]), 1.5);
+    assert!((Loss::BinaryCrossEntropy.compute(&[0.5], &[1.0]) - 2f64.ln()).abs() < 1e-12);
+    assert!((Loss::CrossEntropy.compute(&[0.0, 0.0], &[0.0, 1.0]) - 2f64.ln()).abs() < 1e-12);
+}
@@ -0,0 +1,175 @@
+use crate::cell::Genome;
+
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+pub type Permutation = Vec<usize>;
+
+/*  Permutations are encoded in genomes with one gene per position, the item at a position being
+ *  the rank of its gene in the genome (random keys). Any genome decodes to a valid permutation,
+ *  the genomes created by the permutation operators have the genes (item + 0.5) / n. */
+pub fn to_genome(perm: &Permutation) -> Genome {
+    let n = perm.len() as f64;
+    perm.iter().map(|p| (*p as f64 + 0.5) / n).collect()
+}
+
+pub fn from_genome(genome: &Genome) -> Permutation {
+    let mut order: Vec<usize> = (0..genome.len()).collect();
+    order.sort_by(|a, b| genome[*a].partial_cmp(&genome[*b]).unwrap_or(Ordering::Equal));
+    let mut perm = vec![0; genome.len()];
+    for (rank, pos) in order.iter().enumerate() {
+        perm[*pos] = rank;
+    }
+    perm
+}
+
+pub fn random_permutation(n: usize, rng: &mut ThreadRng) -> Permutation {
+    let mut perm: Permutation = (0..n).collect();
+    perm.shuffle(rng);
+    perm
+}
+
+pub fn is_permutation(perm: &Permutation, n: usize) -> bool {
+    let mut seen = vec![false; n];
+    (perm.len() == n) && perm.iter().all(|p| (*p < n) && !std::mem::replace(&mut seen[*p], true))
+}
+
+// Two cut points a < b, delimiting the segment a..b
+fn cut_points(n: usize, rng: &mut ThreadRng) -> (usize, usize) {
+    let a = rng.gen_range(0..n);
+    (a, rng.gen_range((a + 1)..=n))
+}
+
+// Position of each item in the permutation
+fn positions(perm: &Permutation) -> Vec<usize> {
+    let mut pos = vec![0; perm.len()];
+    for (n, p) in perm.iter().enumerate() {
+        pos[*p] = n;
+    }
+    pos
+}
+
+#[derive(Copy, Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub enum PermutationCrossover {
+    Pmx,    // Partially mapped crossover
+    Order,  // OX, segment of a parent, relative order of the other one
+    Cycle,  // CX, positions taken from the parents cycle by cycle
+}
+
+impl PermutationCrossover {
+    pub fn apply(&self, p1: &Permutation, p2: &Permutation, rng: &mut ThreadRng) -> Permutation {
+        if p1.len() < 2 {
+            return p1.clone();
+        }
+        match self {
+            PermutationCrossover::Pmx => pmx(p1, p2, rng),
+            PermutationCrossover::Order => order_crossover(p1, p2, rng),
+            PermutationCrossover::Cycle => cycle_crossover(p1, p2),
+        }
+    }
+}
+
+pub fn pmx(p1: &Permutation, p2: &Permutation, rng: &mut ThreadRng) -> Permutation {
+    let (a, b) = cut_points(p1.len(), rng);
+    let pos1 = positions(p1);
+    let mut child = p1.clone();
+    for i in (0..a).chain(b..p1.len()) {
+        let mut item = p2[i];
+        while (a..b).contains(&pos1[item]) {
+            item = p2[pos1[item]];
+        }
+        child[i] = item;
+    }
+    child
+}
+
+pub fn order_crossover(p1: &Permutation, p2: &Permutation, rng: &mut ThreadRng) -> Permutation {
+    let n = p1.len();
+    let (a, b) = cut_points(n, rng);
+    let mut used = vec![false; n];
+    p1[a..b].iter().for_each(|p| used[*p] = true);
+    let mut child = p1.clone();
+    let mut items = (0..n).map(|i| p2[(b + i) % n]).filter(|p| !used[*p]);
+    for i in 0..(n - (b - a)) {
+        child[(b + i) % n] = items.next().unwrap();
+    }
+    child
+}
+
+pub fn cycle_crossover(p1: &Permutation, p2: &Permutation) -> Permutation {
+    let pos1 = positions(p1);
+    let mut child = vec![usize::MAX; p1.len()];
+    let mut ncycle = 0;
+    for start in 0..p1.len() {
+        if child[start] != usize::MAX {
+            continue;
+        }
+        let parent = if ncycle % 2 == 0 { p1 } else { p2 };
+        let mut i = start;
+        loop {
+            child[i] = parent[i];
+            i = pos1[p2[i]];
+            if i == start {
+                break;
+            }
+        }
+        ncycle += 1;
+    }
+    child
+}
+
+#[derive(Copy, Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub enum PermutationMutation {
+    Swap,       // Exchange of two items
+    Insert,     // An item moved to another position
+    Inversion,  // Segment reversed
+}
+
+impl PermutationMutation {
+    pub fn apply(&self, perm: &mut Permutation, rng: &mut ThreadRng) {
+        if perm.len() < 2 {
+            return;
+        }
+        match self {
+            PermutationMutation::Swap => {
+                let (a, b) = (rng.gen_range(0..perm.len()), rng.gen_range(0..perm.len()));
+                perm.swap(a, b);
+            }
+            PermutationMutation::Insert => {
+                let item = perm.remove(rng.gen_range(0..perm.len()));
+                perm.insert(rng.gen_range(0..=perm.len()), item);
+            }
+            PermutationMutation::Inversion => {
+                let (a, b) = cut_points(perm.len(), rng);
+                perm[a..b].reverse();
+            }
+        }
+    }
+}
+
+#[test]
+fn test_permutation_operators() {
+    let mut rng = rand::thread_rng();
+    let perm = vec![2, 0, 3, 1];
+    assert_eq!(from_genome(&to_genome(&perm)), perm);
+    assert_eq!(from_genome(&vec![0.7, 0.1, 0.9, 0.3]), perm);
+
+    // Cycles {0, 1}, {2, 3} and {4, 5}, taken alternatively from each parent
+    let p1 = vec![0, 1, 2, 3, 4, 5];
+    let p2 = vec![1, 0, 3, 2, 5, 4];
+    assert_eq!(cycle_crossover(&p1, &p2), vec![0, 1, 3, 2, 4, 5]);
+    for _ in 0..100 {
+        let (a, b) = (random_permutation(10, &mut rng), random_permutation(10, &mut rng));
+        for crossover in [PermutationCrossover::Pmx, PermutationCrossover::Order, PermutationCrossover::Cycle].iter() {
+            assert!(is_permutation(&crossover.apply(&a, &b, &mut rng), 10));
+            assert_eq!(crossover.apply(&a, &a, &mut rng), a);
+        }
+        for mutation in [PermutationMutation::Swap, PermutationMutation::Insert, PermutationMutation::Inversion].iter() {
+            let mut mutant = a.clone();
+            mutation.apply(&mut mutant, &mut rng);
+            assert!(is_permutation(&mutant, 10));
+        }
+    }
+    assert!(!is_permutation(&vec![0, 2, 2], 3));
+}
@@ -0,0 +1,106 @@
+use crate::algo::{AlgoConfiguration, AlgoPopulation};
+use crate::errors::Errcode;
+use crate::genalgomethods::GenalgoMethodsAvailable;
+
+/*  Population and number of elites of each algo of a map.
+ *  Fixed sizes are taken first from the lab population, the rest is shared between the
+ *  weighted algos proportionally to their weight, rounded with the largest remainder method
+ *  so that the whole lab population is used.
+ *  Each algo must reach the minimum size of its method, given its own elites and the ones
+ *  received from the algos giving it their best cells. */
+pub fn plan_populations(
+    map: &[AlgoConfiguration],
+    npop: usize,
+    elite_ratio: f64,
+) -> Result<Vec<(usize, usize)>, Errcode> {
+    if !(0.0..=1.0).contains(&elite_ratio) {
+        return Err(Errcode::ValidationError("elite_ratio not in range (0, 1)"));
+    }
+    let mut fixed = 0;
+    let mut sumweights = 0.0;
+    for cfg in map.iter() {
+        match cfg.population {
+            AlgoPopulation::FixedSize(s) => fixed += s,
+            AlgoPopulation::WeightofTot(w) if w.is_finite() && w > 0.0 => sumweights += w,
+            AlgoPopulation::WeightofTot(_) => return Err(Errcode::InvalidPopulationWeight(cfg.id.clone())),
+        }
+    }
+    if fixed > npop {
+        return Err(Errcode::InsuffisantPopulation(npop, fixed));
+    }
+
+    let rest = npop - fixed;
+    let mut pops = vec![];
+    let mut remainders = vec![];
+    for (n, cfg) in map.iter().enumerate() {
+        match cfg.population {
+            AlgoPopulation::FixedSize(s) => pops.push(s),
+            AlgoPopulation::WeightofTot(w) => {
+                let quota = (rest as f64) * w / sumweights;
+                pops.push(quota.floor() as usize);
+                remainders.push((n, quota - quota.floor()));
+            }
+        }
+    }
+    let left = rest - (pops.iter().sum::<usize>() - fixed);
+    remainders.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap().then(a.0.cmp(&b.0)));
+    for (n, _) in remainders.iter().take(left) {
+        pops[*n] += 1;
+    }
+
+    let mut methods = vec![];
+    let mut elites = vec![];
+    for (cfg, pop) in map.iter().zip(pops.iter()) {
+        let method = match GenalgoMethodsAvailable::get_by_name(&cfg.method) {
+            Some(m) => m,
+            None => return Err(Errcode::ValidationError("genalgo method")),
+        };
+        elites.push((((*pop as f64) * elite_ratio) as usize).max(method.min_elites()));
+        methods.push(method);
+    }
+    let mut received = vec![0; map.len()];
+    for (n, cfg) in map.iter().enumerate() {
+        for togive in cfg.give.iter() {
+            match received.get_mut(*togive) {
+                Some(r) => *r += elites[n],
+                None => return Err(Errcode::IdDoesntExist(*togive)),
+            }
+        }
+    }
+    for (n, cfg) in map.iter().enumerate() {
+        let min = methods[n].min_population(elites[n] + received[n]);
+        if pops[n] < min {
+            return Err(Errcode::AlgoPopulationTooSmall(cfg.id.clone(), pops[n], min));
+        }
+    }
+    Ok(pops.into_iter().zip(elites).collect())
+}
+
+#[test]
+fn test_plan_populations() {
+    let algo = |id: &str, method: &str, population: AlgoPopulation, give: Vec<usize>| {
+        let mut cfg = AlgoConfiguration::method_default(method.to_string()).unwrap();
+        cfg.id = id.to_string();
+        cfg.population = population;
+        cfg.give = give;
+        cfg
+    };
+    let map = vec![
+        algo("A", "Darwin", AlgoPopulation::WeightofTot(1.0), vec![]),
+        algo("B", "Darwin", AlgoPopulation::WeightofTot(1.0), vec![0]),
+        algo("C", "Darwin", AlgoPopulation::WeightofTot(1.0), vec![0]),
+        algo("R", "RandomOpti", AlgoPopulation::FixedSize(10), vec![0]),
+    ];
+    let plan = plan_populations(&map, 110, 0.1).unwrap();
+    assert_eq!(plan.iter().map(|p| p.0).sum::<usize>(), 110);
+    assert_eq!(plan, vec![(34, 3), (33, 3), (33, 3), (10, 1)]);
+
+    // The output algo receives 3 elites, plus its own one
+    assert!(matches!(plan_populations(&map, 25, 0.1),
+        Err(Errcode::AlgoPopulationTooSmall(id, 5, 6)) if id == "A"));
+    assert_eq!(plan_populations(&map, 27, 0.1).unwrap()[0], (6, 1));
+    assert!(matches!(plan_populations(&map, 5, 0.1), Err(Errcode::InsuffisantPopulation(5, 10))));
+    let mut map = map;
+    map[1].population = AlgoPopulation::WeightofTot(-1.0);
+    assert!(matches!(plan_populations(&map, 110, 0.1), Err(Errcode::InvalidPopulationWeight(_))));
+}
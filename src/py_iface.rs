@@ -14,11 +14,18 @@ macro_rules! generate_py_ifaces {
         use $petridish::lab::LabConfig;
         use $petridish::utils::JsonData;
         use $petridish::errors::Errcode;
-        use $petridish::dataset::EmptyDataset;
+        use $petridish::dataset::{EmptyDataset, CsvDataset, JsonLinesDataset, BinaryDataset,
+            DatasetColumns, DatasetRole, PyDataset};
         use $petridish::cell::Cell;
         use $petridish::algo::{AlgoConfiguration, Algo, AlgoID};
         use $petridish::genalgomethods::GenalgoMethodsAvailable;
         use $petridish::labmaps::LabMapAssistant;
+        use $petridish::labmaps::mapfile::LabMapFile;
+        use $petridish::labmaps::graph::MapGraph;
+        use $petridish::sensitivity::SensitivityConfig;
+        use $petridish::restart::archive_to_json;
+        use $petridish::migration::GenomeAdapter;
+        use $petridish::seeding::SeedFill;
 
         $(
             paste!{
@@ -58,6 +65,131 @@ macro_rules! generate_py_ifaces {
                             );
                     }
                     
+                    // Any iterable of rows (list, 2D numpy array, generator, ...), or a function
+                    // returning one
+                    pub fn register_dataset(&mut self, py: Python, id: String, data: PyObject
+                        ) -> PyResult<()>{
+                        let dataset = PyDataset::new(py, data)?;
+                        self.genalgo.register_dataset(id, Box::new(dataset));
+                        Ok(())
+                    }
+
+                    pub fn remove_dataset(&mut self, id: String){
+                        py_err_if_fail!(self.genalgo.remove_dataset(id));
+                    }
+
+                    // List of (id, role) of the registered datasets
+                    pub fn list_datasets(&self) -> Vec<(String, String)>{
+                        self.genalgo.list_datasets().into_iter()
+                            .map(|(id, role)| (id, format!("{:?}", role)))
+                            .collect()
+                    }
+
+                    // Empty columns and column_names lists to use all the columns
+                    pub fn register_csv_dataset(&mut self, id: String, path: String,
+                        delimiter: char, header: bool, columns: Vec<usize>,
+                        column_names: Vec<String>){
+                        let columns = py_err_if_fail!(DatasetColumns::from_lists(columns,
+                            column_names));
+                        self.genalgo.register_dataset(id, Box::new(py_err_if_fail!(
+                            CsvDataset::new(path, delimiter, header, columns))));
+                    }
+
+                    pub fn register_jsonl_dataset(&mut self, id: String, path: String,
+                        columns: Vec<usize>, keys: Vec<String>){
+                        let columns = py_err_if_fail!(DatasetColumns::from_lists(columns, keys));
+                        self.genalgo.register_dataset(id, Box::new(py_err_if_fail!(
+                            JsonLinesDataset::new(path, columns))));
+                    }
+
+                    pub fn register_binary_dataset(&mut self, id: String, path: String,
+                        ncols: usize, columns: Vec<usize>){
+                        let columns = py_err_if_fail!(DatasetColumns::from_lists(columns,
+                            vec![]));
+                        self.genalgo.register_dataset(id, Box::new(py_err_if_fail!(
+                            BinaryDataset::new(path, ncols, columns))));
+                    }
+
+                    // "Training" or "Validation"
+                    pub fn set_dataset_role(&mut self, id: String, role: String){
+                        let role = py_err_if_fail!(DatasetRole::from_name(&role));
+                        py_err_if_fail!(self.genalgo.set_dataset_role(id, role));
+                    }
+
+                    pub fn get_run_history(&self) -> JsonData{
+                        py_err_if_fail!(self.genalgo.get_run_history().to_json())
+                    }
+
+                    // Mean distance between genomes and entropy of each gene at the last generation
+                    pub fn get_diversity(&self, id: AlgoID) -> JsonData{
+                        py_err_if_fail!(py_err_if_fail!(self.genalgo.get_diversity(id)).to_json())
+                    }
+
+                    // Best distinct cells seen in all the algos, with their algo and generation
+                    pub fn get_hall_of_fame(&self) -> JsonData{
+                        py_err_if_fail!(self.genalgo.get_hall_of_fame().to_json())
+                    }
+
+                    pub fn export_lab(&self) -> JsonData{
+                        py_err_if_fail!(self.genalgo.export_lab())
+                    }
+
+                    pub fn import_lab(&mut self, data: JsonData){
+                        py_err_if_fail!(self.genalgo.import_lab(data));
+                    }
+
+                    // Seed genomes of the algo (all algos if None), the rest of its population
+                    // being filled as set by fill (see seeding::SeedFill)
+                    pub fn import_seeds(&mut self, seeds: JsonData, fill: JsonData, algo_id: Option<AlgoID>){
+                        let fill = py_err_if_fail!(SeedFill::from_json(&fill));
+                        py_err_if_fail!(self.genalgo.import_seeds(&seeds, fill, algo_id));
+                    }
+
+                    pub fn import_seeds_from_file(&mut self, path: String, fill: JsonData,
+                        algo_id: Option<AlgoID>){
+                        let fill = py_err_if_fail!(SeedFill::from_json(&fill));
+                        py_err_if_fail!(self.genalgo.import_seeds_from_file(&path, fill, algo_id));
+                    }
+
+                    pub fn seed_from_hall_of_fame(&mut self, fill: JsonData, algo_id: Option<AlgoID>){
+                        let fill = py_err_if_fail!(SeedFill::from_json(&fill));
+                        py_err_if_fail!(self.genalgo.seed_from_hall_of_fame(fill, algo_id));
+                    }
+
+                    // Adapter of genomes saved with from_version, to the following version
+                    pub fn register_genome_adapter(&mut self, from_version: u64, adapter: JsonData){
+                        let adapter = py_err_if_fail!(GenomeAdapter::from_json(adapter));
+                        self.genalgo.register_genome_adapter(from_version, adapter);
+                    }
+
+                    // Number of restarts of each algo
+                    pub fn get_restart_counts(&self) -> Vec<usize>{
+                        self.genalgo.get_restart_counts()
+                    }
+
+                    pub fn get_restart_archive(&self) -> JsonData{
+                        py_err_if_fail!(archive_to_json(self.genalgo.get_restart_archive()))
+                    }
+
+                    // None if the evaluation cache is disabled
+                    pub fn get_cache_stats(&self) -> Option<JsonData>{
+                        self.genalgo.get_cache_stats().map(|s| py_err_if_fail!(s.to_json()))
+                    }
+
+                    pub fn is_overfitting(&self, patience: usize) -> bool{
+                        self.genalgo.get_run_history().is_overfitting(patience)
+                    }
+
+                    pub fn shuffle_dataset(&mut self, id: String){
+                        py_err_if_fail!(self.genalgo.shuffle_dataset(id));
+                    }
+
+                    pub fn sample_dataset(&mut self, id: String, batch_size: usize,
+                        label_column: Option<usize>, reevaluate_elites: bool){
+                        py_err_if_fail!(self.genalgo.sample_dataset(id, batch_size, label_column,
+                            reevaluate_elites));
+                    }
+
                     pub fn set_output_algorithm(&mut self, ind: AlgoID){
                         self.genalgo.set_output_algorithm(ind);
                     }
@@ -66,6 +198,30 @@ macro_rules! generate_py_ifaces {
                         py_err_if_fail!(self.genalgo.apply_json_map(map));
                     }
 
+                    pub fn analyse_sensitivity(&mut self, algo_id: AlgoID, config: JsonData
+                        ) -> JsonData{
+                        let config: SensitivityConfig = py_err_if_fail!(
+                            SensitivityConfig::from_json(config));
+                        py_err_if_fail!(py_err_if_fail!(self.genalgo
+                            .analyse_sensitivity(algo_id, &config)).to_json())
+                    }
+
+                    pub fn export_map_dot(&self) -> String{
+                        self.genalgo.export_map_dot()
+                    }
+
+                    pub fn export_map_mermaid(&self) -> String{
+                        self.genalgo.export_map_mermaid()
+                    }
+
+                    pub fn apply_toml_map(&mut self, map: String){
+                        py_err_if_fail!(self.genalgo.apply_toml_map(map));
+                    }
+
+                    pub fn apply_yaml_map(&mut self, map: String){
+                        py_err_if_fail!(self.genalgo.apply_yaml_map(map));
+                    }
+
 
                     $(
 
@@ -74,6 +230,29 @@ macro_rules! generate_py_ifaces {
                                 .apply_map_with_algo::<$algotype>(map));
                         }
 
+                        pub fn [<import_lab_with_algo_ $algoname>](&mut self, data: JsonData){
+                            py_err_if_fail!(self.genalgo
+                                .import_lab_with_algo::<$algotype>(data));
+                        }
+
+                        pub fn [<apply_toml_map_with_algo_ $algoname>](&mut self, map: String){
+                            py_err_if_fail!(self.genalgo
+                                .apply_toml_map_with_algo::<$algotype>(map));
+                        }
+
+                        pub fn [<apply_yaml_map_with_algo_ $algoname>](&mut self, map: String){
+                            py_err_if_fail!(self.genalgo
+                                .apply_yaml_map_with_algo::<$algotype>(map));
+                        }
+
+                        pub fn [<analyse_sensitivity_with_algo_ $algoname>](&mut self,
+                            config: JsonData) -> JsonData{
+                            let config: SensitivityConfig = py_err_if_fail!(
+                                SensitivityConfig::from_json(config));
+                            py_err_if_fail!(py_err_if_fail!(self.genalgo
+                                .analyse_sensitivity_with_algo::<$algotype>(&config)).to_json())
+                        }
+
                         pub fn [<register_algo_ $algoname>](&mut self) -> usize{
                             py_err_if_fail!(self.genalgo.lab.register_new_algo(
                                 Box::new(<$algotype as Algo>::new())
@@ -105,11 +284,38 @@ macro_rules! generate_py_ifaces {
             py_err_if_fail!(LabConfig::default().to_json())
         }
 
+        #[pyfunction]
+        pub fn get_sensitivity_default() -> JsonData{
+            py_err_if_fail!(SensitivityConfig::default().to_json())
+        }
+
         #[pyfunction]
         pub fn get_algo_default() -> JsonData{
             py_err_if_fail!(AlgoConfiguration::default().to_json())
         }
 
+        #[pyfunction]
+        pub fn map_file_to_json(data: String, format: String) -> JsonData{
+            let mapfile = match format.as_str() {
+                "toml" => py_err_if_fail!(LabMapFile::from_toml(&data)),
+                "yaml" => py_err_if_fail!(LabMapFile::from_yaml(&data)),
+                _ => raise_python_error!("Map file format must be \"toml\" or \"yaml\""),
+            };
+            py_err_if_fail!(mapfile.to_json_map())
+        }
+
+        #[pyfunction]
+        pub fn map_to_dot(map: JsonData, labcfg: Option<JsonData>) -> String{
+            let labcfg = labcfg.map(|cfg| py_err_if_fail!(LabConfig::from_json(cfg)));
+            py_err_if_fail!(MapGraph::from_json_map(&map, labcfg.as_ref())).to_dot()
+        }
+
+        #[pyfunction]
+        pub fn map_to_mermaid(map: JsonData, labcfg: Option<JsonData>) -> String{
+            let labcfg = labcfg.map(|cfg| py_err_if_fail!(LabConfig::from_json(cfg)));
+            py_err_if_fail!(MapGraph::from_json_map(&map, labcfg.as_ref())).to_mermaid()
+        }
+
         #[pyfunction]
         pub fn create_labmap_assistant(mapformat: String) -> LabMapAssistant {
             LabMapAssistant::new(mapformat)
@@ -120,6 +326,10 @@ macro_rules! generate_py_ifaces {
             m.add_function(wrap_pyfunction!(get_lab_default, m)?).unwrap();
             m.add_function(wrap_pyfunction!(create_labmap_assistant, m)?).unwrap();
             m.add_function(wrap_pyfunction!(get_algo_default, m)?).unwrap();
+            m.add_function(wrap_pyfunction!(map_file_to_json, m)?).unwrap();
+            m.add_function(wrap_pyfunction!(get_sensitivity_default, m)?).unwrap();
+            m.add_function(wrap_pyfunction!(map_to_dot, m)?).unwrap();
+            m.add_function(wrap_pyfunction!(map_to_mermaid, m)?).unwrap();
             $(
                 paste! {
                     m.add_function(wrap_pyfunction!([<create_lab_ $name>], m)?).unwrap();
@@ -133,7 +343,7 @@ macro_rules! generate_py_ifaces {
 #[macro_export]
 macro_rules! raise_python_error{
     [$msg:expr] => {
-        panic!($msg)    //TODO raise_python_error!
+        panic!("{}", $msg)    //TODO raise_python_error!
     }
 }
 
@@ -0,0 +1,205 @@
+use crate::algo::AlgoID;
+use crate::cell::{CellData, Genome, Score};
+use crate::errors::Errcode;
+use crate::utils::JsonData;
+
+use rand::prelude::*;
+use rand_distr::Normal;
+use serde::{Deserialize, Serialize};
+
+/*  Population re-initialisation of the algos which stagnate
+ *      Random: new random population
+ *      AroundBest: population spread around the best cell found so far by the lab
+ *      IPOP: new random population, bigger by factor at each restart
+ *      BIPOP: alternates IPOP restarts with restarts of random smaller populations,
+ *          choosing the regime which used the fewest evaluations so far */
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum RestartPolicy {
+    Random,
+    AroundBest { sigma: f64 },
+    IPOP { factor: f64 },
+    BIPOP { factor: f64 },
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct RestartConfig {
+    pub policy: RestartPolicy,
+    pub stagnation: usize,      // Generations without improvement before a restart
+    pub tolerance: f64,         // Minimal score change considered as an improvement
+    pub max_pop_factor: f64,
+}
+
+impl Default for RestartConfig {
+    fn default() -> RestartConfig {
+        RestartConfig {
+            policy: RestartPolicy::Random,
+            stagnation: 20,
+            tolerance: 1e-9,
+            max_pop_factor: 16.0,
+        }
+    }
+}
+
+impl RestartConfig {
+    pub fn validate(&self) -> Result<(), Errcode> {
+        if self.stagnation == 0 {
+            return Err(Errcode::ValidationError("restart: stagnation == 0"));
+        }
+        if self.max_pop_factor < 1.0 {
+            return Err(Errcode::ValidationError("restart: max_pop_factor < 1"));
+        }
+        match self.policy {
+            RestartPolicy::AroundBest { sigma } if sigma <= 0.0 => {
+                Err(Errcode::ValidationError("restart: sigma <= 0"))
+            }
+            RestartPolicy::IPOP { factor } | RestartPolicy::BIPOP { factor } if factor < 1.0 => {
+                Err(Errcode::ValidationError("restart: population factor < 1"))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+// Best cell of an algo before one of its restarts
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ArchivedCell {
+    pub algo: AlgoID,
+    pub restart: usize,
+    pub generation: usize,
+    pub cell: CellData,
+}
+
+// Stagnation tracking and population size of an algo
+#[derive(Clone, Debug)]
+pub struct RestartState {
+    pub nrestarts: usize,
+    pub pop_factor: f64,
+    best: Option<CellData>,
+    stagnating: usize,
+    large_factor: f64,
+    large_budget: f64,
+    small_budget: f64,
+    small_regime: bool,
+}
+
+impl Default for RestartState {
+    fn default() -> RestartState {
+        RestartState {
+            nrestarts: 0,
+            pop_factor: 1.0,
+            best: None,
+            stagnating: 0,
+            large_factor: 1.0,
+            large_budget: 0.0,
+            small_budget: 0.0,
+            small_regime: false,
+        }
+    }
+}
+
+impl RestartState {
+    // Returns true if the algo has to be restarted
+    pub fn update(&mut self, best: &CellData, config: &RestartConfig, maximize: bool, pop: usize) -> bool {
+        if self.small_regime {
+            self.small_budget += pop as f64;
+        } else {
+            self.large_budget += pop as f64;
+        }
+        let improved = match &self.best {
+            Some(b) if maximize => best.score > b.score + config.tolerance,
+            Some(b) => best.score < b.score - config.tolerance,
+            None => true,
+        };
+        if improved {
+            self.best = Some(best.clone());
+            self.stagnating = 0;
+        } else {
+            self.stagnating += 1;
+        }
+        self.stagnating >= config.stagnation
+    }
+
+    // Archives the best cell and computes the population size of the restarted algo
+    pub fn restart(&mut self, config: &RestartConfig, algo: AlgoID, generation: usize) -> Option<ArchivedCell> {
+        let archived = self.best.take().map(|cell| ArchivedCell {
+            algo,
+            restart: self.nrestarts,
+            generation,
+            cell,
+        });
+        self.nrestarts += 1;
+        self.stagnating = 0;
+        match config.policy {
+            RestartPolicy::Random | RestartPolicy::AroundBest { .. } => {}
+            RestartPolicy::IPOP { factor } => {
+                self.pop_factor = (self.pop_factor * factor).min(config.max_pop_factor);
+            }
+            RestartPolicy::BIPOP { factor } => {
+                self.small_regime = self.small_budget < self.large_budget;
+                if self.small_regime {
+                    let u: f64 = rand::thread_rng().gen();
+                    self.pop_factor = self.large_factor.powf(u * u);
+                } else {
+                    self.large_factor = (self.large_factor * factor).min(config.max_pop_factor);
+                    self.pop_factor = self.large_factor;
+                }
+            }
+        }
+        archived
+    }
+}
+
+// Best genome kept as is, the other ones drawn around it, genes kept in [0, 1]
+pub fn genomes_around(best: &Genome, sigma: f64, nb: usize) -> Vec<Genome> {
+    let mut rng = rand::thread_rng();
+    let normal = Normal::new(0.0, sigma).unwrap();
+    let mut res = vec![best.clone()];
+    for _ in 1..nb {
+        res.push(best.iter().map(|g| (g + normal.sample(&mut rng)).clamp(0.0, 1.0)).collect());
+    }
+    res.truncate(nb);
+    res
+}
+
+pub fn archive_to_json(archive: &[ArchivedCell]) -> Result<JsonData, Errcode> {
+    Ok(serde_json::to_string(archive)?)
+}
+
+// Best archived cell of an algo
+pub fn best_archived(archive: &[ArchivedCell], algo: AlgoID, maximize: bool) -> Option<&CellData> {
+    archive.iter()
+        .filter(|a| a.algo == algo)
+        .map(|a| &a.cell)
+        .fold(None, |best: Option<&CellData>, c| match best {
+            Some(b) if (maximize && b.score >= c.score) || (!maximize && b.score <= c.score) => Some(b),
+            _ => Some(c),
+        })
+}
+
+pub fn score_is_better(a: Score, b: Score, maximize: bool) -> bool {
+    if maximize { a > b } else { a < b }
+}
+
+#[test]
+fn test_restart_state() {
+    let cell = |score: f64| CellData { genome: vec![0.5], score, version: 0 };
+    let config = RestartConfig {
+        policy: RestartPolicy::IPOP { factor: 2.0 },
+        stagnation: 2,
+        tolerance: 0.0,
+        max_pop_factor: 3.0,
+    };
+    let mut state = RestartState::default();
+    assert!(!state.update(&cell(1.0), &config, false, 10));
+    assert!(!state.update(&cell(1.0), &config, false, 10));
+    assert!(state.update(&cell(1.5), &config, false, 10));
+    let archived = state.restart(&config, 0, 3).unwrap();
+    assert_eq!((archived.restart, archived.cell.score), (0, 1.0));
+    assert_eq!(state.pop_factor, 2.0);
+    state.restart(&config, 0, 4);
+    assert_eq!((state.nrestarts, state.pop_factor), (2, 3.0));
+
+    let genomes = genomes_around(&vec![0.0, 1.0], 0.1, 5);
+    assert_eq!(genomes.len(), 5);
+    assert!(genomes.iter().flatten().all(|g| (0.0..=1.0).contains(g)));
+}
@@ -0,0 +1,226 @@
+use crate::cell::{Cell, Genome};
+use crate::errors::Errcode;
+
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/*  Sampling of the genomes created from scratch (initial population, random cells)
+ *      Uniform: independent uniform genes
+ *      LatinHypercube: each gene spread over n strata of [0, 1]
+ *      Sobol / Halton: low-discrepancy sequences, randomly shifted at each sampling
+ *      Opposition: half uniform genomes, half their opposites (1 - gene) */
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+pub enum InitStrategy {
+    #[default]
+    Uniform,
+    LatinHypercube,
+    Sobol,
+    Halton,
+    Opposition,
+}
+
+impl InitStrategy {
+    pub fn is_default(&self) -> bool {
+        *self == InitStrategy::Uniform
+    }
+
+    pub fn validate(&self, ngenes: usize) -> Result<(), Errcode> {
+        if *self == InitStrategy::Sobol && ngenes > SOBOL_MAX_DIM {
+            return Err(Errcode::SizeError("sobol dimensions", SOBOL_MAX_DIM, ngenes));
+        }
+        Ok(())
+    }
+
+    pub fn sample(&self, n: usize, ngenes: usize) -> Vec<Genome> {
+        match self {
+            InitStrategy::Uniform => uniform(n, ngenes),
+            InitStrategy::LatinHypercube => latin_hypercube(n, ngenes),
+            InitStrategy::Sobol => sobol(n, ngenes),
+            InitStrategy::Halton => halton(n, ngenes),
+            InitStrategy::Opposition => opposition(n, ngenes),
+        }
+    }
+
+    // Genomes of a cell type, variable-length ones are cut at a random length in the bounds
+    pub fn sample_genomes<T: Cell>(&self, n: usize) -> Vec<Genome> {
+        match T::genome_length_bounds() {
+            None => self.sample(n, T::get_genome_length()),
+            Some((min, max)) => {
+                let mut rng = rand::thread_rng();
+                let mut res = self.sample(n, max);
+                for genome in res.iter_mut() {
+                    genome.truncate(rng.gen_range(min..=max));
+                }
+                res
+            }
+        }
+    }
+}
+
+pub fn uniform(n: usize, ngenes: usize) -> Vec<Genome> {
+    let mut rng = rand::thread_rng();
+    (0..n).map(|_| (0..ngenes).map(|_| rng.gen()).collect()).collect()
+}
+
+/*  Latin hypercube: for each gene, [0, 1] is cut in n strata and each stratum holds
+ *  the gene of exactly one genome, at a random place inside it. */
+pub fn latin_hypercube(n: usize, ngenes: usize) -> Vec<Genome> {
+    let mut rng = rand::thread_rng();
+    let mut res = vec![Genome::with_capacity(ngenes); n];
+    for _ in 0..ngenes {
+        let mut strata: Vec<usize> = (0..n).collect();
+        strata.shuffle(&mut rng);
+        for (genome, stratum) in res.iter_mut().zip(strata) {
+            genome.push(((stratum as f64) + rng.gen::<f64>()) / (n as f64));
+        }
+    }
+    res
+}
+
+pub fn opposition(n: usize, ngenes: usize) -> Vec<Genome> {
+    let mut res = uniform(n - (n / 2), ngenes);
+    let opposites: Vec<Genome> = res.iter().take(n / 2).map(|g| g.iter().map(|x| 1.0 - x).collect()).collect();
+    res.extend(opposites);
+    res
+}
+
+/*  Primitive polynomials (degree s, coefficients a) and initial direction numbers m of
+ *  the dimensions after the first one, from Joe & Kuo. */
+const SOBOL_DIRECTIONS: [(u32, u32, &[u32]); 20] = [
+    (1, 0, &[1]),
+    (2, 1, &[1, 3]),
+    (3, 1, &[1, 3, 1]),
+    (3, 2, &[1, 1, 1]),
+    (4, 1, &[1, 1, 3, 3]),
+    (4, 4, &[1, 3, 5, 13]),
+    (5, 2, &[1, 1, 5, 5, 17]),
+    (5, 4, &[1, 1, 5, 5, 5]),
+    (5, 7, &[1, 1, 7, 11, 19]),
+    (5, 11, &[1, 1, 5, 1, 1]),
+    (5, 13, &[1, 1, 1, 3, 11]),
+    (5, 14, &[1, 3, 5, 5, 31]),
+    (6, 1, &[1, 3, 3, 9, 7, 49]),
+    (6, 13, &[1, 1, 1, 15, 21, 21]),
+    (6, 16, &[1, 3, 1, 13, 27, 49]),
+    (6, 19, &[1, 1, 1, 15, 7, 5]),
+    (6, 22, &[1, 3, 1, 15, 13, 25]),
+    (6, 25, &[1, 1, 5, 5, 19, 61]),
+    (7, 1, &[1, 3, 7, 11, 23, 15, 103]),
+    (7, 4, &[1, 3, 7, 13, 13, 15, 69]),
+];
+const SOBOL_MAX_DIM: usize = SOBOL_DIRECTIONS.len() + 1;
+const SOBOL_BITS: usize = 32;
+
+fn sobol_directions(dim: usize) -> [u32; SOBOL_BITS] {
+    let mut v = [0u32; SOBOL_BITS];
+    if dim == 0 {
+        for (k, vk) in v.iter_mut().enumerate() {
+            *vk = 1 << (SOBOL_BITS - 1 - k);
+        }
+        return v;
+    }
+    let (s, a, m) = SOBOL_DIRECTIONS[dim - 1];
+    let s = s as usize;
+    for k in 0..SOBOL_BITS {
+        v[k] = if k < s {
+            m[k] << (SOBOL_BITS - 1 - k)
+        } else {
+            let mut vk = v[k - s] ^ (v[k - s] >> s);
+            for j in 1..s {
+                if (a >> (s - 1 - j)) & 1 == 1 {
+                    vk ^= v[k - j];
+                }
+            }
+            vk
+        };
+    }
+    v
+}
+
+// Points 0 to n - 1 of the sequence (Gray code order), with a random digital shift per gene
+pub fn sobol(n: usize, ngenes: usize) -> Vec<Genome> {
+    // Dimensions beyond the table are refused by validate, Halton takes over if called anyway
+    if ngenes > SOBOL_MAX_DIM {
+        return halton(n, ngenes);
+    }
+    let mut rng = rand::thread_rng();
+    let directions: Vec<[u32; SOBOL_BITS]> = (0..ngenes).map(sobol_directions).collect();
+    let shifts: Vec<u32> = (0..ngenes).map(|_| rng.gen()).collect();
+    let mut x = vec![0u32; directions.len()];
+    let mut res = vec![];
+    for i in 0..n {
+        res.push(x.iter().zip(shifts.iter()).map(|(xd, sh)| ((xd ^ sh) as f64) / 2f64.powi(32)).collect());
+        let c = (!i).trailing_zeros() as usize % SOBOL_BITS;
+        for (xd, v) in x.iter_mut().zip(directions.iter()) {
+            *xd ^= v[c];
+        }
+    }
+    res
+}
+
+fn primes(n: usize) -> Vec<u64> {
+    let mut res: Vec<u64> = vec![];
+    let mut candidate = 2;
+    while res.len() < n {
+        if res.iter().take_while(|p| *p * *p <= candidate).all(|p| candidate % p != 0) {
+            res.push(candidate);
+        }
+        candidate += 1;
+    }
+    res
+}
+
+fn radical_inverse(mut i: u64, base: u64) -> f64 {
+    let mut res = 0.0;
+    let mut f = 1.0 / (base as f64);
+    while i > 0 {
+        res += f * ((i % base) as f64);
+        i /= base;
+        f /= base as f64;
+    }
+    res
+}
+
+// Points 1 to n of the sequence, with a random rotation (modulo 1) per gene
+pub fn halton(n: usize, ngenes: usize) -> Vec<Genome> {
+    let mut rng = rand::thread_rng();
+    let bases = primes(ngenes);
+    let shifts: Vec<f64> = (0..ngenes).map(|_| rng.gen()).collect();
+    (1..=n as u64)
+        .map(|i| bases.iter().zip(shifts.iter()).map(|(b, sh)| (radical_inverse(i, *b) + sh) % 1.0).collect())
+        .collect()
+}
+
+#[test]
+fn test_latin_hypercube() {
+    let genomes = latin_hypercube(10, 3);
+    assert_eq!(genomes.len(), 10);
+    for gene in 0..3 {
+        let mut strata: Vec<usize> = genomes.iter().map(|g| (g[gene] * 10.0) as usize).collect();
+        strata.sort_unstable();
+        assert_eq!(strata, (0..10).collect::<Vec<usize>>());
+    }
+}
+
+#[test]
+fn test_low_discrepancy_samplers() {
+    // Without the random shift, the 4 first Sobol points of a gene hit each quarter of [0, 1] once
+    let v = sobol_directions(1);
+    let mut x = 0u32;
+    let mut quarters = vec![];
+    for i in 0..4usize {
+        quarters.push(((x as f64) / 2f64.powi(32) * 4.0) as usize);
+        x ^= v[(!i).trailing_zeros() as usize];
+    }
+    quarters.sort_unstable();
+    assert_eq!(quarters, vec![0, 1, 2, 3]);
+
+    assert_eq!(radical_inverse(3, 2), 0.75);
+    assert_eq!(primes(5), vec![2, 3, 5, 7, 11]);
+    for strategy in [InitStrategy::Sobol, InitStrategy::Halton, InitStrategy::Opposition].iter() {
+        let genomes = strategy.sample(9, 4);
+        assert_eq!(genomes.len(), 9);
+        assert!(genomes.iter().all(|g| g.len() == 4 && g.iter().all(|x| (0.0..1.0).contains(x))));
+    }
+    assert!(InitStrategy::Sobol.validate(SOBOL_MAX_DIM + 1).is_err());
+}
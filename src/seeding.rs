@@ -0,0 +1,116 @@
+use crate::cell::{Cell, Genome};
+use crate::errors::Errcode;
+use crate::sampling::InitStrategy;
+
+use rand::prelude::*;
+use rand_distr::Normal;
+use serde::{Deserialize, Serialize};
+
+/*  How the population of an algo is completed once its seed genomes are placed
+ *      Mutants: copies of the seeds, taken in turn, with a normal noise on every gene
+ *      LatinHypercube: genomes spread over the whole genome space
+ *      Random: uniformly random genomes */
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum SeedFill {
+    Mutants { sigma: f64 },
+    LatinHypercube,
+    Random,
+}
+
+impl Default for SeedFill {
+    fn default() -> SeedFill {
+        SeedFill::Mutants { sigma: 0.1 }
+    }
+}
+
+impl SeedFill {
+    pub fn from_json(jsdata: &str) -> Result<SeedFill, Errcode> {
+        Ok(serde_json::from_str(jsdata)?)
+    }
+
+    pub fn validate(&self) -> Result<(), Errcode> {
+        match self {
+            SeedFill::Mutants { sigma } if *sigma <= 0.0 => Err(Errcode::ValidationError("seed fill: sigma <= 0")),
+            _ => Ok(()),
+        }
+    }
+}
+
+// Seed genome with the version of the cell it was saved from, if known
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SeedItem {
+    Genome(Genome),
+    Cell { genome: Genome, version: Option<u64> },
+    HallOfFame { cell: Box<SeedItem> },
+}
+
+impl SeedItem {
+    fn into_genome(self) -> (Genome, Option<u64>) {
+        match self {
+            SeedItem::Genome(genome) => (genome, None),
+            SeedItem::Cell { genome, version } => (genome, version),
+            SeedItem::HallOfFame { cell } => cell.into_genome(),
+        }
+    }
+}
+
+/*  Seeds from a JSON list of genomes, of cells (with their version) or of hall of fame
+ *  entries, or from a lab export whose hall of fame is used. */
+pub fn seeds_from_json(jsdata: &str) -> Result<Vec<(Genome, Option<u64>)>, Errcode> {
+    let data: serde_json::Value = serde_json::from_str(jsdata)?;
+    let items = match data.get("hall_of_fame") {
+        Some(hof) => hof.clone(),
+        None => data,
+    };
+    let items: Vec<SeedItem> = serde_json::from_value(items)?;
+    Ok(items.into_iter().map(|i| i.into_genome()).collect())
+}
+
+/*  The seeds first (as many as fit), then the genomes of the fill strategy, sampled as the
+ *  genomes created from scratch for the cell type (see InitStrategy::sample_genomes) */
+pub fn seed_population<T: Cell>(seeds: &[Genome], pop: usize, fill: &SeedFill) -> Vec<Genome> {
+    let mut res: Vec<Genome> = seeds.iter().take(pop).cloned().collect();
+    let nfill = pop - res.len();
+    match fill {
+        SeedFill::Mutants { sigma } if !seeds.is_empty() => {
+            let mut rng = rand::thread_rng();
+            let normal = Normal::new(0.0, *sigma).unwrap();
+            for seed in seeds.iter().cycle().take(nfill) {
+                res.push(seed.iter().map(|g| (g + normal.sample(&mut rng)).clamp(0.0, 1.0)).collect());
+            }
+        }
+        SeedFill::LatinHypercube => res.extend(InitStrategy::LatinHypercube.sample_genomes::<T>(nfill)),
+        _ => res.extend(InitStrategy::Uniform.sample_genomes::<T>(nfill)),
+    }
+    res
+}
+
+#[test]
+fn test_seed_population() {
+    use crate::cell::{genome_length_is_valid, test_cell};
+    test_cell!(SeedCell, (), 2);
+    test_cell!(VarCell, (), 2, bounds: (1, 6));
+
+    let seeds = seeds_from_json("[[0.1, 0.2], {\"genome\": [0.3, 0.4], \"version\": 2, \"score\": 1.0}]").unwrap();
+    assert_eq!(seeds[1], (vec![0.3, 0.4], Some(2)));
+    let export = "{\"hall_of_fame\": [{\"cell\": {\"genome\": [0.5, 0.5], \"score\": 0.0, \"version\": 1}, \
+        \"algo\": 0, \"generation\": 3}]}";
+    assert_eq!(seeds_from_json(export).unwrap(), vec![(vec![0.5, 0.5], Some(1))]);
+
+    let seeds: Vec<Genome> = seeds.into_iter().map(|s| s.0).collect();
+    let pop = seed_population::<SeedCell>(&seeds, 5, &SeedFill::Mutants { sigma: 0.01 });
+    assert_eq!(pop.len(), 5);
+    assert_eq!(&pop[..2], &seeds[..]);
+    assert!((pop[3][0] - 0.3).abs() < 0.1);
+    assert_eq!(seed_population::<SeedCell>(&seeds, 1, &SeedFill::Random), vec![vec![0.1, 0.2]]);
+    let pop = seed_population::<SeedCell>(&seeds, 4, &SeedFill::LatinHypercube);
+    assert!(pop.len() == 4 && pop.iter().all(|g| g.len() == 2));
+
+    // Fill genomes of variable-length cells get lengths spread over the bounds
+    for fill in [SeedFill::LatinHypercube, SeedFill::Random].iter() {
+        let pop = seed_population::<VarCell>(&seeds, 200, fill);
+        assert!(pop.iter().all(|g| genome_length_is_valid::<VarCell>(g.len())));
+        assert!(pop.iter().filter(|g| g.len() < 6).count() > 100);
+    }
+}
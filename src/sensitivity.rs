@@ -0,0 +1,294 @@
+use crate::cell::{Genome, Score};
+use crate::errors::Errcode;
+use crate::utils::JsonData;
+
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct SensitivityConfig {
+    pub ntrajectories: usize,         // Morris trajectories, (ngenes + 1) evaluations each
+    pub nlevels: usize,               // Levels of the Morris grid
+    pub ninteraction_points: usize,   // Base points for the pairwise interactions estimation
+    pub interaction_threshold: f64,   // Interaction relative to the biggest gene effect
+    pub negligible_threshold: f64,    // Relative sensitivity under which genes are put aside
+    pub max_group_size: usize,
+}
+
+impl Default for SensitivityConfig {
+    fn default() -> SensitivityConfig {
+        SensitivityConfig {
+            ntrajectories: 20,
+            nlevels: 4,
+            ninteraction_points: 5,
+            interaction_threshold: 0.1,
+            negligible_threshold: 0.01,
+            max_group_size: 4,
+        }
+    }
+}
+
+impl SensitivityConfig {
+    pub fn from_json(jsdata: JsonData) -> Result<SensitivityConfig, Errcode> {
+        Ok(serde_json::from_str(&jsdata)?)
+    }
+
+    pub fn to_json(&self) -> Result<JsonData, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    pub fn validate(&self) -> Result<(), Errcode> {
+        if self.ntrajectories < 2 {
+            return Err(Errcode::ValidationError("sensitivity: ntrajectories < 2"));
+        }
+        if self.nlevels < 2 {
+            return Err(Errcode::ValidationError("sensitivity: nlevels < 2"));
+        }
+        if self.max_group_size == 0 {
+            return Err(Errcode::ValidationError("sensitivity: max_group_size == 0"));
+        }
+        Ok(())
+    }
+}
+
+/*  Morris screening of the genes (mean of the absolute elementary effects, and their
+ *  standard deviation), completed by the estimation of the pairwise interactions using
+ *  second order finite differences. Genes interacting together are grouped so that they
+ *  are optimised by the same algo, genes without effect are gathered in a last group. */
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SensitivityAnalysis {
+    pub mu_star: Vec<f64>,
+    pub sigma: Vec<f64>,
+    pub interactions: Vec<Vec<f64>>,
+    pub groups: Vec<Vec<usize>>,
+    pub priorities: Vec<f64>,
+}
+
+impl SensitivityAnalysis {
+    pub fn run<F>(ngenes: usize, config: &SensitivityConfig, mut evaluate: F
+        ) -> Result<SensitivityAnalysis, Errcode>
+    where
+        F: FnMut(&[Genome]) -> Vec<Score>,
+    {
+        config.validate()?;
+        if ngenes == 0 {
+            return Err(Errcode::ValidationError("sensitivity: genome is empty"));
+        }
+        let mut rng = rand::thread_rng();
+        let delta = (config.nlevels as f64) / (2.0 * ((config.nlevels - 1) as f64));
+
+        let (mu_star, sigma) = SensitivityAnalysis::elementary_effects(ngenes, config, delta,
+            &mut evaluate, &mut rng)?;
+        let interactions = SensitivityAnalysis::pairwise_interactions(ngenes, config,
+            delta / 2.0, &mut evaluate, &mut rng)?;
+
+        let mut res = SensitivityAnalysis {
+            mu_star,
+            sigma,
+            interactions,
+            groups: vec![],
+            priorities: vec![],
+        };
+        res.groups = res.group_genes(config);
+        res.priorities = res.groups_priorities();
+        Ok(res)
+    }
+
+    fn elementary_effects<F>(ngenes: usize, config: &SensitivityConfig, delta: f64,
+        evaluate: &mut F, rng: &mut ThreadRng) -> Result<(Vec<f64>, Vec<f64>), Errcode>
+    where
+        F: FnMut(&[Genome]) -> Vec<Score>,
+    {
+        let step = 1.0 / ((config.nlevels - 1) as f64);
+        let mut genomes = vec![];
+        let mut changed = vec![];
+        for _ in 0..config.ntrajectories {
+            let mut point: Genome = (0..ngenes)
+                .map(|_| (rng.gen_range(0..config.nlevels) as f64) * step)
+                .collect();
+            let mut order: Vec<usize> = (0..ngenes).collect();
+            order.shuffle(rng);
+            genomes.push(point.clone());
+            for gene in order {
+                let dir = if point[gene] + delta <= 1.0 { delta } else { -delta };
+                point[gene] += dir;
+                genomes.push(point.clone());
+                changed.push((gene, dir));
+            }
+        }
+        let scores = SensitivityAnalysis::checked_evaluate(evaluate, &genomes)?;
+
+        let mut effects = vec![vec![]; ngenes];
+        for (t, chunk) in scores.chunks(ngenes + 1).enumerate() {
+            for (n, pair) in chunk.windows(2).enumerate() {
+                let (gene, dir) = changed[(t * ngenes) + n];
+                effects[gene].push((pair[1] - pair[0]) / dir);
+            }
+        }
+        let mut mu_star = vec![];
+        let mut sigma = vec![];
+        for eff in effects.iter() {
+            let nb = eff.len() as f64;
+            let mean = eff.iter().sum::<f64>() / nb;
+            mu_star.push(eff.iter().map(|e| e.abs()).sum::<f64>() / nb);
+            sigma.push((eff.iter().map(|e| (e - mean).powi(2)).sum::<f64>() / (nb - 1.0)).sqrt());
+        }
+        Ok((mu_star, sigma))
+    }
+
+    fn pairwise_interactions<F>(ngenes: usize, config: &SensitivityConfig, step: f64,
+        evaluate: &mut F, rng: &mut ThreadRng) -> Result<Vec<Vec<f64>>, Errcode>
+    where
+        F: FnMut(&[Genome]) -> Vec<Score>,
+    {
+        let mut interactions = vec![vec![0.0; ngenes]; ngenes];
+        if ngenes < 2 || config.ninteraction_points == 0 {
+            return Ok(interactions);
+        }
+        // For each point: f(x), f(x + hi) for every i, then f(x + hi + hj) for every i < j
+        let mut genomes = vec![];
+        for _ in 0..config.ninteraction_points {
+            let base: Genome = (0..ngenes).map(|_| rng.gen_range(0.0..(1.0 - step))).collect();
+            genomes.push(base.clone());
+            for i in 0..ngenes {
+                let mut g = base.clone();
+                g[i] += step;
+                genomes.push(g);
+            }
+            for i in 0..ngenes {
+                for j in (i + 1)..ngenes {
+                    let mut g = base.clone();
+                    g[i] += step;
+                    g[j] += step;
+                    genomes.push(g);
+                }
+            }
+        }
+        let scores = SensitivityAnalysis::checked_evaluate(evaluate, &genomes)?;
+
+        let point_size = 1 + ngenes + ((ngenes * (ngenes - 1)) / 2);
+        for chunk in scores.chunks(point_size) {
+            let mut pair_ind = 1 + ngenes;
+            for i in 0..ngenes {
+                for j in (i + 1)..ngenes {
+                    let inter = (chunk[pair_ind] - chunk[1 + i] - chunk[1 + j] + chunk[0]).abs()
+                        / (step * step * (config.ninteraction_points as f64));
+                    interactions[i][j] += inter;
+                    interactions[j][i] += inter;
+                    pair_ind += 1;
+                }
+            }
+        }
+        Ok(interactions)
+    }
+
+    fn checked_evaluate<F>(evaluate: &mut F, genomes: &[Genome]) -> Result<Vec<Score>, Errcode>
+    where
+        F: FnMut(&[Genome]) -> Vec<Score>,
+    {
+        let scores = evaluate(genomes);
+        if scores.len() != genomes.len() {
+            return Err(Errcode::SizeError("sensitivity scores", genomes.len(), scores.len()));
+        }
+        if scores.iter().any(|s| !s.is_finite()) {
+            return Err(Errcode::ValidationError("sensitivity: non-finite score"));
+        }
+        Ok(scores)
+    }
+
+    // Greedy agglomeration of the most interacting pairs, bounded by max_group_size
+    pub fn group_genes(&self, config: &SensitivityConfig) -> Vec<Vec<usize>> {
+        let ngenes = self.mu_star.len();
+        let max_mu = self.mu_star.iter().cloned().fold(0.0, f64::max);
+        let negligible: Vec<bool> = self.mu_star.iter()
+            .map(|m| (max_mu == 0.0) || (*m / max_mu < config.negligible_threshold))
+            .collect();
+
+        // Interactions relative to the biggest gene effect over the whole genes range
+        let mut pairs = vec![];
+        for i in 0..ngenes {
+            for j in (i + 1)..ngenes {
+                let rel = if max_mu > 0.0 { self.interactions[i][j] / max_mu } else { 0.0 };
+                if !negligible[i] && !negligible[j] && rel >= config.interaction_threshold {
+                    pairs.push((rel, i, j));
+                }
+            }
+        }
+        pairs.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
+
+        let mut group_of: Vec<usize> = (0..ngenes).collect();
+        let mut groups: Vec<Vec<usize>> = (0..ngenes).map(|g| vec![g]).collect();
+        for (_, i, j) in pairs {
+            let (gi, gj) = (group_of[i], group_of[j]);
+            if gi == gj || groups[gi].len() + groups[gj].len() > config.max_group_size {
+                continue;
+            }
+            let moved = std::mem::take(&mut groups[gj]);
+            for g in moved.iter() {
+                group_of[*g] = gi;
+            }
+            groups[gi].extend(moved);
+        }
+
+        let mut res: Vec<Vec<usize>> = groups.into_iter()
+            .filter(|g| !g.is_empty() && !negligible[g[0]])
+            .map(|mut g| { g.sort_unstable(); g })
+            .collect();
+        let rest: Vec<usize> = (0..ngenes).filter(|g| negligible[*g]).collect();
+        if !rest.is_empty() {
+            res.push(rest);
+        }
+        res
+    }
+
+    // Relative sensitivity of each group, in (0.5, 1)
+    pub fn groups_priorities(&self) -> Vec<f64> {
+        let sums: Vec<f64> = self.groups.iter()
+            .map(|g| g.iter().map(|gene| self.mu_star[*gene]).sum())
+            .collect();
+        let max = sums.iter().cloned().fold(0.0, f64::max);
+        sums.iter().map(|s| if max > 0.0 { 0.5 + (0.5 * s / max) } else { 1.0 }).collect()
+    }
+
+    pub fn to_json(&self) -> Result<JsonData, Errcode> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    // Analyses may be edited by hand, one priority per group and genes of the analysis only
+    pub fn from_json(jsdata: &str) -> Result<SensitivityAnalysis, Errcode> {
+        let analysis: SensitivityAnalysis = serde_json::from_str(jsdata)?;
+        if analysis.groups.len() != analysis.priorities.len() {
+            return Err(Errcode::SizeError("sensitivity priorities", analysis.groups.len(),
+                analysis.priorities.len()));
+        }
+        if analysis.groups.iter().flatten().any(|gene| *gene >= analysis.mu_star.len()) {
+            return Err(Errcode::ValidationError("sensitivity analysis: gene index out of range"));
+        }
+        Ok(analysis)
+    }
+}
+
+#[test]
+fn test_sensitivity_gene_groups() {
+    // Genes 0 and 1 interacting, 2 alone, 3 without any effect
+    let fct = |genomes: &[Genome]| -> Vec<Score> {
+        genomes.iter().map(|g| (4.0 * g[0] * g[1]) + g[2]).collect()
+    };
+    let analysis = SensitivityAnalysis::run(4, &SensitivityConfig::default(), fct).unwrap();
+    assert!(analysis.mu_star[3] < 1e-9);
+    assert!(analysis.interactions[0][1] > 1.0);
+    assert!(analysis.interactions[0][2] < 1e-6);
+    assert_eq!(analysis.groups, vec![vec![0, 1], vec![2], vec![3]]);
+    assert_eq!(analysis.priorities.len(), 3);
+
+    let jsdata = analysis.to_json().unwrap();
+    assert_eq!(SensitivityAnalysis::from_json(&jsdata).unwrap().groups, analysis.groups);
+    let mut wrong = analysis.clone();
+    wrong.priorities.pop();
+    assert!(matches!(SensitivityAnalysis::from_json(&wrong.to_json().unwrap()),
+        Err(Errcode::SizeError(_, 3, 2))));
+    wrong = analysis;
+    wrong.groups[2].push(4);
+    assert!(matches!(SensitivityAnalysis::from_json(&wrong.to_json().unwrap()),
+        Err(Errcode::ValidationError(_))));
+}